@@ -3,9 +3,323 @@ error-invalid-config-dir = Config directory "{$dir}" is not valid.
 error-invalid-repo-dir = Repository directory "{$dir}" is not valid.
 error-no-default-open-with = No default open with program specified.
 error-target-not-found = Target not found.
+error-target-not-found-suggest = Target not found, did you mean: {$candidates}?
+error-target-ambiguous = {$target} matches several repositories: {$candidates}
+error-unknown-group = No group named {$group}.
+error-nested-group = Group {$group} contains group {$target}, groups cannot contain groups.
 error-not-supported-system-setup-for-os = Current OS do not support system setup.
 error-get-base-dirs = Can not get base dirs.
 error-no-editor-specified = No editor specified.
 error-no-repo-dir-specified = No repository directory specified.
 error-not-supported-operation-for-os = Current OS do not support this operation.
 info-setup-completed = Setup completed: "{$file}"
+cli-about = A repository manager.
+cli-heading-usage = Usage:
+cli-heading-commands = Commands
+cli-heading-arguments = Arguments
+cli-heading-options = Options
+cli-arg-help = Print help
+cli-arg-version = Print version
+cli-arg-system = Use the system level setup
+cli-arg-user = Use the user level setup
+cli-arg-local = Use the local level setup in the current directory
+cli-arg-config = Use a custom config file
+cli-arg-porcelain = Print stable tab separated records for scripts
+cli-arg-color = When to color the output, `auto` honors NO_COLOR
+cli-arg-timeout = Seconds after which a git process is killed, overrides the config
+cli-clone-about = Clone a repository into the repository directory
+cli-clone-arg-type = Repository type, `git`, `vendor` for archives, or a type provided by a plugin
+cli-clone-arg-target = Url of the repository to clone, a shorthand like github.com/owner/repo, or a git bundle file
+cli-clone-arg-origin-url = Url of the origin, required to place repositories cloned from bundles
+cli-clone-arg-json = Print the result as json instead of the output of git
+cli-adopt-about = Move an existing repository into the layout of the given url
+cli-adopt-arg-url = Url of the repository, decides where it is placed and becomes the origin if it has none
+cli-adopt-arg-path = Directory of the existing repository
+cli-update-about = Pull git repositories and refresh vendored archives
+cli-update-arg-json = Print the results as a json array instead of a line per repository
+cli-update-arg-relocate = Follow repositories that moved upstream without asking, rewriting origin and moving their directory
+cli-init-about = Write the config, asking for the basic settings, and create the repository directory
+cli-init-arg-force = Overwrite an existing config instead of only adding missing settings
+cli-open-about = Open a repository with a program
+cli-open-arg-devcontainer = Open the repository in its devcontainer
+cli-open-arg-with = Opener profile or program to open the repository with
+cli-open-arg-target = Repository to open
+cli-enter-about = Run the dev environment command of a repository in it, or a shell
+cli-enter-arg-with = Command to run instead of the configured one
+cli-enter-arg-target = Repository to enter
+cli-browse-about = Open the web page of a repository on its forge
+cli-browse-arg-commit = Open the page of a commit
+cli-browse-arg-pr = Open the page of a pull request
+cli-browse-arg-file = Open the page of a file, at the commit checked out
+cli-browse-arg-line = Line of the file to jump to
+cli-browse-arg-copy = Copy the url to the clipboard instead of opening it
+cli-browse-arg-target = Repository to browse
+cli-copy-about = Copy the directory or the url of a repository to the clipboard
+cli-copy-arg-url = Copy the url the repository was cloned from
+cli-copy-arg-path = Copy the absolute directory of the repository, the default
+cli-copy-arg-target = Repository to copy the directory or url of
+cli-config-about = Manage the config file
+cli-config-arg-edit = Edit the config file
+cli-config-arg-with = Editor to edit the config file with
+cli-config-migrate-about = Upgrade the config file to the current format, keeping a backup
+cli-discover-about = List the repositories starred on a forge, marking the cloned ones, and clone the others
+cli-discover-arg-starred = List the repositories starred by the owner of the access token, the default
+cli-discover-arg-watched = List the watched repositories instead of the starred ones
+cli-discover-arg-all = Clone all of them that are not cloned instead of asking
+cli-discover-arg-host = Host of the forge, like github.com
+cli-create-about = Create a new repository
+cli-create-arg-type = Repository type
+cli-create-arg-hostname = Hostname to place the repository under, default_host of the config or localhost if omitted
+cli-create-arg-target = Path of the repository to create, optionally starting with the hostname like github.com/owner/name, owned by the user of the access token for the host if it has no owner
+cli-create-arg-from = Existing directory to turn into the repository, initializing git in it if needed
+cli-create-arg-publish = Also create the repository on the forge of the host and push it
+cli-create-arg-template = Project template to fill the repository with, from the templates of the config, the templates directory next to it, or a git url like gh:owner/name, prefixed with cookiecutter: or copier: for templates of those tools
+cli-create-arg-var = Value of a variable of the template, asked for or taken from its default otherwise
+cli-create-arg-json = Print the result as json instead of the output of git
+cli-list-about = List repositories
+cli-arg-filter-type = Only include repositories whose type contains this value
+cli-arg-filter-hostname = Only include repositories whose hostname contains this value
+cli-arg-filter-owner = Only include repositories whose owner contains this value
+cli-arg-filter-user = Only include repositories cloned by a user whose name contains this value
+cli-arg-filter-path = Only include repositories whose path contains this value
+cli-arg-filter-devcontainer = Only include repositories with a devcontainer configuration
+cli-arg-filter-lang = Only include repositories whose primary language, detected from files like Cargo.toml or go.mod, is this one
+cli-arg-filter-script = Only include repositories for which this rhai script returns true
+cli-arg-filter-where = Only include repositories matching this rhai expression over their fields, like 'host == "github.com" && dirty && size > 100MB'
+cli-arg-group = Only include repositories of this group
+cli-list-arg-json = Print the list as json
+cli-list-arg-columns = Comma separated columns to show, in order
+cli-list-arg-long = Also show who cloned the repositories and their description and topics from the forge
+cli-list-arg-no-cache = Scan the repositories instead of reading the index
+cli-list-arg-format = Print each repository through a template like "{"{"}path{"}"}\t{"{"}hostname{"}"}", with the column names as fields
+cli-search-about = Search repositories by path, description and topics
+cli-search-arg-query = Text to search for, case insensitive
+cli-refresh-meta-about = Fetch descriptions and topics of repositories from their forge
+cli-status-about = Show the branch and working tree state of repositories
+cli-status-arg-format = Print each repository through a template like "{"{"}path{"}"}: {"{"}branch{"}"}", with path, type, hostname, branch and status as fields
+cli-mirror-about = Push repositories to an additional mirror remote
+cli-mirror-add-about = Add a mirror remote to a repository
+cli-mirror-add-arg-name = Name of the mirror remote
+cli-mirror-add-arg-target = Repository to add the mirror to
+cli-mirror-add-arg-url = Url of the mirror
+cli-mirror-push-about = Push all branches and tags of repositories to their mirror
+cli-mirror-push-arg-name = Name of the mirror remote, repositories without it are skipped
+cli-sync-fork-about = Fast-forward the default branch of forks to upstream and push it to origin
+cli-sync-fork-arg-target = Repository to sync, all matching forks with an upstream remote if omitted
+cli-apply-about = Run a script on a new branch of every clean repository and commit its changes
+cli-apply-arg-script = Shell command, executable or rhai script ending in .rhai, run in each repository with it in RERMAN_* variables
+cli-apply-arg-branch = Branch to make the change on
+cli-apply-arg-message = Message of the commits, "Apply <script>" by default
+cli-apply-arg-pr = Push the branches and open pull requests for them
+cli-commit-all-about = Commit with the same message in every dirty repository with staged changes
+cli-commit-all-arg-message = Message of the commits
+cli-commit-all-arg-add = Pathspec to stage before committing, may be repeated
+cli-push-about = Push the current branch of repositories to their upstream, or to origin
+cli-push-arg-force-with-lease = Also push branches that are not fast-forwards, unless the remote branch changed since it was fetched
+cli-remotes-about = Manage the remotes of repositories
+cli-remotes-list-about = List all remotes of repositories
+cli-remotes-add-about = Add a remote to a repository
+cli-remotes-add-arg-target = Repository to add the remote to
+cli-remotes-add-arg-name = Name of the remote
+cli-remotes-add-arg-url = Url of the remote
+cli-remotes-rm-about = Remove a remote from a repository
+cli-remotes-rm-arg-target = Repository to remove the remote from
+cli-remotes-rm-arg-name = Name of the remote
+cli-manifest-about = Exchange the repository inventory with other tools
+cli-manifest-export-about = Print the repositories with their urls as a manifest
+cli-manifest-export-arg-format = Format of the manifest: rerman, mr (myrepos), git-workspace or repo-xml (Google's repo tool)
+cli-manifest-import-about = Clone the repositories of a manifest that are missing
+cli-manifest-import-arg-format = Format of the manifest: rerman, git-workspace or repo-xml, guessed if omitted
+cli-manifest-import-arg-file = Manifest file
+cli-manifest-export-arg-commits = Also record the commit each git repository is at, for diff-manifest to compare
+cli-audit-about = Check the repositories for compliance
+cli-audit-signatures-about = Check whether the recent commits and tags of the default branches are signed with known keys
+cli-audit-signatures-arg-commits = Number of recent commits to check in each repository
+cli-audit-signatures-arg-tags = Number of recent tags to check in each repository
+cli-audit-signatures-arg-json = Print the report as json
+cli-audit-secrets-about = Scan the working trees for secrets with gitleaks, or with built-in rules if it is not installed
+cli-audit-secrets-arg-builtin = Use the built-in rules even if gitleaks is installed
+cli-audit-secrets-arg-json = Print the findings as json
+cli-audit-deps-about = List the dependencies locked by Cargo.lock, package-lock.json and go.sum files with the number of repositories using them
+cli-audit-deps-arg-contains = List the repositories using this package and its versions instead
+cli-audit-deps-arg-json = Print the inventory as json
+cli-diff-manifest-about = Report the repositories missing locally, those not in a manifest, and those cloned from other urls or at other commits
+cli-diff-manifest-arg-format = Format of the manifest: rerman, git-workspace or repo-xml, guessed if omitted
+cli-diff-manifest-arg-json = Print the differences as json
+cli-diff-manifest-arg-file = Manifest file
+cli-resume-about = Continue the last interrupted bulk clone, retrying the failed repositories
+cli-cache-about = Keep bare mirrors that clones borrow objects from
+cli-cache-warm-about = Create or update the mirror of a repository
+cli-cache-warm-arg-url = Url or shorthand of the repository
+cli-cache-serve-about = Serve the mirrors over the git protocol
+cli-cache-serve-arg-port = Port to listen on
+cli-timeline-about = Show recent commits of repositories, newest first
+cli-timeline-arg-since = Only show commits newer than this date, in any format git understands
+cli-timeline-arg-author = Only show commits whose author matches this pattern
+cli-timeline-arg-json = Print the commits as json
+cli-branches-about = Show the current branch and the number of local and prunable branches of repositories
+cli-branches-prune-about = Delete local branches merged into the default branch or gone upstream
+cli-branches-prune-arg-apply = Delete the branches instead of only printing them
+cli-layout-about = Manage where repositories are placed
+cli-layout-migrate-about = Move git repositories to the directory of their origin url, the same for ssh and https
+cli-layout-migrate-arg-apply = Move the repositories instead of only printing them
+cli-watch-about = Keep the repository index up to date until interrupted
+cli-jobs-about = Run the recurring operations of the config
+cli-jobs-list-about = List the jobs with their last run
+cli-jobs-run-about = Run a job and record how it went
+cli-jobs-run-arg-name = Name of the job
+cli-subrepo-about = Manage directories of repositories registered as repositories of their own
+cli-subrepo-add-about = Register a directory of a repository, like a service of a monorepo, to be targeted as <target>/<path>
+cli-subrepo-add-arg-target = Repository containing the directory
+cli-subrepo-add-arg-path = Path of the directory in the repository
+cli-template-about = Manage project templates
+cli-template-update-about = Pull the remote templates that were cloned
+cli-template-update-arg-template = Template to update, all cloned templates if omitted
+cli-group-about = Manage named groups of repositories
+cli-group-list-about = List the groups and their repositories
+cli-group-add-about = Add targets to a group, creating it if needed
+cli-group-add-arg-name = Name of the group
+cli-group-add-arg-targets = Targets to add, anything but another group
+cli-exec-about = Run a command in each repository
+cli-exec-arg-output = How to show the output: interleaved lines prefixed with the repository, grouped per repository, or files with a log per repository
+cli-exec-arg-jobs = Number of repositories to run the command in at once, the number of CPUs by default
+cli-exec-arg-command = Command and its arguments
+cli-self-update-about = Update rerman to the latest release
+cli-self-update-arg-check = Only check whether a newer release exists
+cli-debug-about = Debugging helpers
+cli-debug-locale-about = Print the detected system locale
+cli-debug-locale-text-about = Print a localized text
+cli-debug-locale-text-arg-key = Key of the text
+cli-help-about = Print this message or the help of the given subcommand(s)
+cli-error = error: {$message}
+cli-error-unknown-argument = unexpected argument '{$arg}' found
+cli-error-invalid-subcommand = unrecognized subcommand '{$arg}'
+cli-error-missing-required-argument = the following required arguments were not provided: {$arg}
+cli-error-invalid-value = invalid value '{$value}' for '{$arg}'
+cli-error-missing-subcommand = a subcommand is required but one was not provided
+cli-error-argument-conflict = the argument '{$arg}' cannot be used with '{$prior}'
+cli-error-tip-similar = tip: a similar value exists: '{$value}'
+cli-error-try-help = For more information, try '--help'.
+status-dirty = dirty
+status-clean = clean
+error-no-release-asset = The latest release has no verifiable binary for this platform.
+info-up-to-date = Already up to date: {$current}
+info-update-available = A new version is available: {$current} -> {$latest}
+info-updated = Updated: {$current} -> {$latest}
+info-update-hint = rerman {$latest} is available (current: {$current}), run `rerman self-update` to update.
+info-mirror-pushed = Pushed {$repo}
+error-mirror-push-failed = Push of {$repo} failed: {$error}
+error-some-repos-failed = {$count} repositories failed.
+info-exec-group = ==> {$repo} <==
+error-exec-failed = {$repo} failed: {$error}
+info-exec-logs = Logs are in "{$dir}"
+info-exec-log = {$repo}: {$file}
+error-unknown-repo-type = No plugin provides the repository type {$type}.
+error-unknown-forge = No plugin provides the forge {$forge}.
+error-hook-failed = Hook {$hook} in "{$dir}" failed: {$error}
+error-exec-failed-log = {$repo} failed: {$error}, see {$file}
+error-bundle-needs-origin-url = Cloning a bundle requires --origin-url to know where the repository belongs.
+info-repo-updated = Updated {$repo}
+info-repo-up-to-date = {$repo} is up to date
+info-repo-no-upstream = {$repo} has no upstream, skipped
+error-repo-update-failed = Update of {$repo} failed: {$error}
+info-repo-moved = Moved to "{$dir}"
+error-read-only-system-root = "{$dir}" is not writable. Run this with sudo, or use your own repositories with --user true.
+error-read-only-root = "{$dir}" is not writable.
+prompt-follow-move = {$repo} moved to {$url} upstream. Rewrite origin and move it there?
+info-repo-moved-upstream = {$repo} moved to {$url} upstream, update with --relocate to follow it.
+info-repo-relocated = Relocated {$repo} to "{$dir}"
+error-not-a-git-repo = "{$dir}" is not a git repository.
+error-refresh-meta-failed = Fetching metadata of {$repo} failed: {$error}
+info-fork-synced = Synced {$repo}: {$branch}
+error-fork-needs-merge = {$repo} diverged from upstream, {$branch} needs a manual merge
+error-fork-sync-failed = Sync of {$repo} failed: {$error}
+info-apply-unchanged = {$repo} is unchanged by the script
+info-apply-committed = Committed the change to {$repo} on {$branch}
+info-apply-pr-opened = Opened a pull request for {$repo}: {$url}
+info-apply-prs = { $count ->
+    [one] Opened one pull request:
+   *[other] Opened {$count} pull requests:
+}
+error-apply-dirty = the repository has uncommitted changes
+error-apply-failed = Applying to {$repo} failed: {$error}
+info-committed = Committed {$repo}: {$commit}
+info-commits-created = Created {$count} commits.
+error-commit-failed = Commit in {$repo} failed: {$error}
+info-pushed = Pushed {$repo}: {$branch}
+info-push-up-to-date = {$repo} is up to date: {$branch}
+info-push-detached = Skipped {$repo}, it is not on a branch
+error-push-rejected = {$repo} was not pushed, {$branch} is not a fast-forward, see --force-with-lease
+error-push-failed = Push of {$repo} failed: {$error}
+error-forks-need-merge = Forks needing a manual merge: {$repos}
+error-enter-failed = Command failed with {$status}.
+error-no-devcontainer = {$repo} has no devcontainer configuration.
+info-branch-would-prune = Would delete {$repo}: {$branch}
+info-branch-pruned = Deleted {$repo}: {$branch}
+error-branch-prune-failed = Pruning branches of {$repo} failed: {$error}
+info-repo-cloned = Cloned {$repo}
+info-repo-exists = {$repo} already exists, skipped
+error-repo-clone-failed = Clone of {$repo} failed: {$error}
+error-repo-create-failed = Creating {$repo} failed: {$error}
+prompt-discover-clone = Repositories to clone, space to pick
+info-discover-not-cloned = {$count} repositories are not cloned, run with --all to clone them.
+prompt-init-level = Level to set up
+init-level-user = user, for the current account
+init-level-local = local, in the current directory
+prompt-init-repo-dir = Directory to place repositories in
+prompt-init-open-with = Program to open repositories with, empty for none
+prompt-init-default-host = Hostname of repositories made with create
+prompt-init-protocol = Protocol to clone shorthand targets like github.com/owner/repo with
+info-config-key-added = Added {$key}
+info-config-key-kept = Kept the existing value of {$key}
+info-config-backup = Backed up the previous config to "{$file}"
+info-config-unchanged = "{$file}" already has all settings, nothing changed. Use --force to overwrite it.
+info-config-migrated = Migrated "{$file}" from version {$from} to {$to} ({$changes}), the previous config is kept in "{$backup}"
+info-config-current = "{$file}" is at version {$version}, nothing to migrate.
+error-cancelled = Cancelled.
+info-nothing-to-resume = Nothing to resume.
+error-some-repos-failed-resume = {$count} repositories failed, run `rerman resume` to retry them.
+info-cache-warmed = Mirror ready: "{$dir}"
+info-cache-serving = Serving "{$dir}" on port {$port}
+error-no-forge-of-host = The forge of {$host} is unknown, set `forge` of the host in the config.
+info-copied = Copied {$text}
+error-no-url = {$repo} has no url it was cloned from.
+error-create-unsupported-type = Creating repositories of type {$type} is not supported, only git.
+error-unknown-template = There is no template {$template}, expected it in {$dir}.
+error-template-tool-missing = Could not run {$tool}, is it installed? {$error}
+error-template-tool-failed = {$tool} failed: {$status}
+error-template-clone-failed = Cloning the template {$url} failed: {$error}
+error-template-not-remote = The template {$template} is not a repository.
+error-template-update-failed = Updating the template {$template} failed: {$error}
+error-some-templates-failed = {$count} templates could not be updated.
+error-invalid-var = Expected {$var} to be like key=value.
+error-invalid-var-pattern = The pattern of the template variable {$var} is invalid: {$error}
+error-template-var-missing = The template variable {$var} has no default, give it with --var {$var}=value.
+error-audit-unsigned = {$count} repositories have unsigned or unverified commits or tags.
+error-audit-secrets = Found {$count} possible secrets.
+info-audit-no-secrets = No secrets found.
+error-audit-deps-failed = Could not read the lockfiles of {$repo}: {$error}
+info-audit-deps-unused = No repository uses {$name}.
+info-audit-signed = All checked commits and tags are signed with known keys.
+info-manifest-in-sync = The repositories agree with the manifest.
+error-manifest-drift = {$count} differences to the manifest.
+info-subrepo-added = Registered {$path} of {$repo} as a subrepo.
+error-invalid-subrepo = {$path} is not a directory inside {$repo}.
+info-template-updated = Updated the template {$template}.
+error-template-var-mismatch = Expected a value matching {$pattern}
+error-template-var-invalid = The value {$value} of the template variable {$var} does not match {$pattern}.
+error-not-a-dir = "{$dir}" is not a directory.
+error-no-forge-token = Using the API of {$host} needs an access token in {$env}.
+info-repo-published = Published to {$url}
+info-layout-would-move = Would move {$repo} to "{$dir}"
+info-layout-moved = Moved {$repo} to "{$dir}"
+info-layout-in-place = All repositories are in place.
+info-group-added = Added {$targets} to group {$group} in "{$file}"
+info-index-stale = The repository index was out of date, the repositories were scanned again.
+info-watching = Watching the repositories, press Ctrl-C to stop.
+info-job-done = Job {$job} finished after {$seconds}s.
+error-unknown-job = No job named {$job}.
+error-invalid-job = The command of job {$job} is invalid: {$error}
+error-job-failed = Job {$job} failed: {$status}
+info-no-groups = No groups are configured.
+error-layout-move-failed = Moving {$repo} failed: {$error}
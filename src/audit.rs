@@ -0,0 +1,53 @@
+//! Log of changes rerman made to the layout on its own, one JSON object per
+//! line in `audit.log` of the data directory.
+
+use std::{path::Path, time::SystemTime};
+
+use tokio::io::AsyncWriteExt;
+
+#[derive(serde::Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum AuditEvent {
+    /// A repository followed its upstream to a new url.
+    Rename {
+        from_url: String,
+        to_url: String,
+        from_dir: String,
+        to_dir: String,
+    },
+    /// A repository moved to the directory of its url by `layout migrate`.
+    Move {
+        url: String,
+        from_dir: String,
+        to_dir: String,
+    },
+}
+
+#[derive(serde::Serialize)]
+struct AuditEntry<'a> {
+    /// Seconds since the unix epoch.
+    time: u64,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+}
+
+/// Appends `event` to the audit log in `file`.
+pub async fn record(file: &Path, event: &AuditEvent) -> anyhow::Result<()> {
+    let entry = AuditEntry {
+        time: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs(),
+        event,
+    };
+    if let Some(parent) = file.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut log = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)
+        .await?;
+    log.write_all(format!("{}\n", serde_json::to_string(&entry)?).as_bytes())
+        .await?;
+    Ok(())
+}
@@ -1,4 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{
+    error::{ContextKind, ErrorKind},
+    Arg, ArgAction, Args, Command, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
+use unic_langid::LanguageIdentifier;
+
+use crate::{i18n::I18N, manifest::ManifestFormat, porcelain::PorcelainVersion};
 
 #[derive(Parser)]
 #[command(version = "snapshot", about = "A repository manager.", long_about = None)]
@@ -11,6 +17,10 @@ pub struct Cli {
     pub local: Option<bool>,
     #[arg(short, long)]
     pub config: Option<String>,
+    #[arg(long, value_enum, default_value_t = ColorWhen::Auto)]
+    pub color: ColorWhen,
+    #[arg(long)]
+    pub timeout: Option<u64>,
     #[command(subcommand)]
     pub commands: Commands,
 }
@@ -20,15 +30,83 @@ pub enum Commands {
     Clone {
         #[arg(long, default_value = "git")]
         r#type: String,
+        #[arg(long)]
+        origin_url: Option<String>,
+        #[arg(long)]
+        json: bool,
         target: String,
     },
-    Setup,
+    Discover {
+        #[arg(long, conflicts_with = "watched")]
+        starred: bool,
+        #[arg(long)]
+        watched: bool,
+        #[arg(long)]
+        all: bool,
+        host: String,
+    },
+    Adopt {
+        #[arg(long)]
+        url: String,
+        path: String,
+    },
+    Update {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long)]
+        json: bool,
+        #[arg(long)]
+        relocate: bool,
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            default_missing_value = "v1",
+            conflicts_with = "json"
+        )]
+        porcelain: Option<PorcelainVersion>,
+    },
+    #[command(alias = "setup")]
+    Init {
+        #[arg(long)]
+        force: bool,
+    },
     Open {
+        #[arg(long, conflicts_with = "with")]
+        devcontainer: bool,
         #[arg(long)]
         with: Option<String>,
         target: String,
     },
+    Enter {
+        #[arg(long)]
+        with: Option<String>,
+        target: String,
+    },
+    Browse {
+        #[arg(long, conflicts_with_all = ["pr", "file"])]
+        commit: Option<String>,
+        #[arg(long, conflicts_with = "file")]
+        pr: Option<u64>,
+        #[arg(long)]
+        file: Option<String>,
+        #[arg(long, requires = "file")]
+        line: Option<u32>,
+        #[arg(long)]
+        copy: bool,
+        target: String,
+    },
+    Copy {
+        #[arg(long, conflicts_with = "path")]
+        url: bool,
+        #[arg(long)]
+        path: bool,
+        target: String,
+    },
+    #[command(args_conflicts_with_subcommands = true)]
     Config {
+        #[command(subcommand)]
+        commands: Option<ConfigCommands>,
         #[arg(long)]
         edit: bool,
         #[arg(long)]
@@ -37,24 +115,469 @@ pub enum Commands {
     Create {
         #[arg(long, default_value = "git")]
         r#type: String,
-        #[arg(long, default_value = "localhost")]
-        hostname: String,
+        #[arg(long)]
+        hostname: Option<String>,
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long, requires = "from")]
+        publish: bool,
+        #[arg(long, conflicts_with = "from")]
+        template: Option<String>,
+        #[arg(long, value_name = "KEY=VALUE", requires = "template")]
+        var: Vec<String>,
+        #[arg(long)]
+        json: bool,
         target: String,
     },
     List {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long, default_value = "false")]
+        json: bool,
+        #[arg(long, value_enum, value_delimiter = ',')]
+        columns: Option<Vec<RepoColumn>>,
         #[arg(long)]
-        filter_type: Option<String>,
+        long: bool,
+        #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "v1")]
+        porcelain: Option<PorcelainVersion>,
+        #[arg(long, conflicts_with_all = ["json", "porcelain"])]
+        format: Option<String>,
         #[arg(long)]
-        filter_hostname: Option<String>,
+        no_cache: bool,
+    },
+    Search {
+        query: String,
+    },
+    RefreshMeta {
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Status {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "v1")]
+        porcelain: Option<PorcelainVersion>,
+        #[arg(long, conflicts_with = "porcelain")]
+        format: Option<String>,
+    },
+    Mirror {
+        #[command(subcommand)]
+        commands: MirrorCommands,
+    },
+    SyncFork {
+        target: Option<String>,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Apply {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long)]
+        script: String,
+        #[arg(long)]
+        branch: String,
+        #[arg(short, long)]
+        message: Option<String>,
+        #[arg(long)]
+        pr: bool,
+    },
+    CommitAll {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(short, long)]
+        message: String,
+        #[arg(long)]
+        add: Vec<String>,
+    },
+    Push {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long)]
+        force_with_lease: bool,
+    },
+    Remotes {
+        #[command(subcommand)]
+        commands: RemotesCommands,
+    },
+    Manifest {
+        #[command(subcommand)]
+        commands: ManifestCommands,
+    },
+    Audit {
+        #[command(subcommand)]
+        commands: AuditCommands,
+    },
+    DiffManifest {
+        #[arg(long, value_enum)]
+        format: Option<ManifestFormat>,
         #[arg(long)]
-        filter_path: Option<String>,
-        #[arg(long, default_value = "false")]
         json: bool,
+        #[command(flatten)]
+        filter: RepoFilter,
+        file: String,
+    },
+    Resume,
+    Cache {
+        #[command(subcommand)]
+        commands: CacheCommands,
+    },
+    Timeline {
+        #[arg(long, default_value = "1 week ago")]
+        since: String,
+        #[arg(long)]
+        author: Option<String>,
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    #[command(args_conflicts_with_subcommands = true)]
+    Branches {
+        #[command(subcommand)]
+        commands: Option<BranchesCommands>,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Layout {
+        #[command(subcommand)]
+        commands: LayoutCommands,
+    },
+    Group {
+        #[command(subcommand)]
+        commands: GroupCommands,
+    },
+    Watch,
+    Jobs {
+        #[command(subcommand)]
+        commands: JobsCommands,
+    },
+    Template {
+        #[command(subcommand)]
+        commands: TemplateCommands,
+    },
+    Subrepo {
+        #[command(subcommand)]
+        commands: SubrepoCommands,
+    },
+    Exec {
+        #[arg(long, value_enum, default_value_t = ExecOutput::Interleaved)]
+        output: ExecOutput,
+        #[arg(short, long)]
+        jobs: Option<usize>,
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+    SelfUpdate {
+        #[arg(long)]
+        check: bool,
     },
     Debug {
         #[command(subcommand)]
         commands: DebugCommands,
     },
+    /// Unknown subcommands, looked up in the aliases of the config.
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+impl Commands {
+    /// Whether Ctrl-C cancels the command, which it does unless the command
+    /// hands the terminal to a program, like the shell of enter or an editor,
+    /// which gets Ctrl-C instead while rerman waits for it.
+    pub fn cancellable(&self) -> bool {
+        !matches!(
+            self,
+            Commands::Enter { .. } | Commands::Open { .. } | Commands::Config { edit: true, .. }
+        )
+    }
+
+    /// Whether the command changes repositories or the state besides them, so
+    /// that it needs a writable root.
+    pub fn writes(&self) -> bool {
+        match self {
+            Commands::Clone { .. }
+            | Commands::Discover { .. }
+            | Commands::Adopt { .. }
+            | Commands::Update { .. }
+            | Commands::Init { .. }
+            | Commands::Create { .. }
+            | Commands::RefreshMeta { .. }
+            | Commands::SyncFork { .. }
+            | Commands::Apply { .. }
+            | Commands::CommitAll { .. }
+            | Commands::Push { .. }
+            | Commands::Jobs {
+                commands: JobsCommands::Run { .. },
+            }
+            | Commands::Template {
+                commands: TemplateCommands::Update { .. },
+            }
+            | Commands::Subrepo {
+                commands: SubrepoCommands::Add { .. },
+            }
+            | Commands::Mirror {
+                commands: MirrorCommands::Add { .. },
+            }
+            | Commands::Resume
+            | Commands::Watch
+            | Commands::Config {
+                commands: Some(ConfigCommands::Migrate),
+                ..
+            }
+            | Commands::Cache {
+                commands: CacheCommands::Warm { .. },
+            }
+            | Commands::Manifest {
+                commands: ManifestCommands::Import { .. },
+            }
+            | Commands::Group {
+                commands: GroupCommands::Add { .. },
+            } => true,
+            Commands::Remotes { commands } => !matches!(commands, RemotesCommands::List { .. }),
+            Commands::Branches {
+                commands: Some(BranchesCommands::Prune { apply, .. }),
+                ..
+            }
+            | Commands::Layout {
+                commands: LayoutCommands::Migrate { apply, .. },
+            } => *apply,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum MirrorCommands {
+    Add {
+        #[arg(long, default_value = "mirror")]
+        name: String,
+        target: String,
+        url: String,
+    },
+    Push {
+        #[arg(long, default_value = "mirror")]
+        name: String,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RemotesCommands {
+    List {
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Add {
+        target: String,
+        name: String,
+        url: String,
+    },
+    Rm {
+        target: String,
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    Warm {
+        url: String,
+    },
+    Serve {
+        #[arg(long, default_value_t = 9418)]
+        port: u16,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum JobsCommands {
+    List,
+    Run { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    Update { template: Option<String> },
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommands {
+    Signatures {
+        #[arg(long, default_value_t = 20)]
+        commits: usize,
+        #[arg(long, default_value_t = 5)]
+        tags: usize,
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Secrets {
+        #[arg(long)]
+        builtin: bool,
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Deps {
+        #[arg(long)]
+        contains: Option<String>,
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SubrepoCommands {
+    Add { target: String, path: String },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    Migrate,
+}
+
+// parsed once, the size of the filter does not matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand)]
+pub enum ManifestCommands {
+    Export {
+        #[arg(long, value_enum, default_value = "rerman")]
+        format: ManifestFormat,
+        #[arg(long)]
+        commits: bool,
+        #[command(flatten)]
+        filter: RepoFilter,
+    },
+    Import {
+        #[arg(long, value_enum)]
+        format: Option<ManifestFormat>,
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BranchesCommands {
+    Prune {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GroupCommands {
+    List,
+    Add {
+        name: String,
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LayoutCommands {
+    Migrate {
+        #[command(flatten)]
+        filter: RepoFilter,
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+/// How `exec` shows the output of the repositories. The values have no doc
+/// comments, which clap would show untranslated, the localized help of
+/// `--output` describes them.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExecOutput {
+    // lines as they come, prefixed with the repository
+    Interleaved,
+    // the whole output of each repository once it has finished
+    Grouped,
+    // a log file per repository in the data directory
+    Files,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RepoColumn {
+    Path,
+    Type,
+    Hostname,
+    Owner,
+    Branch,
+    User,
+    Description,
+    Topics,
+    Devcontainer,
+    Language,
+}
+
+impl RepoColumn {
+    pub const DEFAULT: &'static [RepoColumn] =
+        &[RepoColumn::Path, RepoColumn::Type, RepoColumn::Hostname];
+    pub const LONG: &'static [RepoColumn] = &[
+        RepoColumn::Path,
+        RepoColumn::Type,
+        RepoColumn::Hostname,
+        RepoColumn::Branch,
+        RepoColumn::User,
+        RepoColumn::Language,
+        RepoColumn::Description,
+        RepoColumn::Topics,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RepoColumn::Path => "path",
+            RepoColumn::Type => "type",
+            RepoColumn::Hostname => "hostname",
+            RepoColumn::Owner => "owner",
+            RepoColumn::Branch => "branch",
+            RepoColumn::User => "user",
+            RepoColumn::Description => "description",
+            RepoColumn::Topics => "topics",
+            RepoColumn::Devcontainer => "devcontainer",
+            RepoColumn::Language => "language",
+        }
+    }
+}
+
+#[derive(Args, Default)]
+pub struct RepoFilter {
+    #[arg(long)]
+    pub filter_type: Option<String>,
+    #[arg(long)]
+    pub filter_hostname: Option<String>,
+    #[arg(long)]
+    pub filter_owner: Option<String>,
+    #[arg(long)]
+    pub filter_user: Option<String>,
+    #[arg(long)]
+    pub filter_path: Option<String>,
+    #[arg(long)]
+    pub filter_devcontainer: bool,
+    #[arg(long)]
+    pub filter_lang: Option<String>,
+    #[arg(long)]
+    pub filter_script: Option<String>,
+    #[arg(long = "where", value_name = "EXPR")]
+    pub filter_where: Option<String>,
+    #[arg(long)]
+    pub group: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -62,3 +585,211 @@ pub enum DebugCommands {
     Locale,
     LocaleText { key: String },
 }
+
+impl Cli {
+    /// Builds the clap command with its help text taken from the Fluent bundles.
+    pub fn localized_command(i18n: &I18N, lang_id: &LanguageIdentifier) -> Command {
+        let mut cmd = localize_command(Cli::command(), "cli", i18n, lang_id);
+        cmd.build();
+        localize_help_subcommand(cmd, i18n, lang_id)
+    }
+
+    /// Parses `args` with the localized command, exiting on errors like clap does.
+    /// Without `allow_external` unknown subcommands are errors.
+    pub fn parse_localized(
+        args: &[String],
+        i18n: &I18N,
+        lang_id: &LanguageIdentifier,
+        allow_external: bool,
+    ) -> Self {
+        let matches = Cli::localized_command(i18n, lang_id)
+            .allow_external_subcommands(allow_external)
+            .try_get_matches_from(args)
+            .unwrap_or_else(|err| match Cli::localized_error(&err, i18n, lang_id) {
+                Some(msg) => {
+                    eprint!("{}", msg);
+                    std::process::exit(err.exit_code())
+                }
+                None => err.exit(),
+            });
+        Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Renders a clap parse error with the messages of the Fluent bundles,
+    /// returns `None` if the error kind has no localized message.
+    pub fn localized_error(
+        err: &clap::Error,
+        i18n: &I18N,
+        lang_id: &LanguageIdentifier,
+    ) -> Option<String> {
+        let context = |kind: ContextKind| err.get(kind).map(|value| value.to_string());
+        let (msg_id, params) = match err.kind() {
+            ErrorKind::UnknownArgument => (
+                "cli-error-unknown-argument",
+                vec![("arg", context(ContextKind::InvalidArg)?)],
+            ),
+            ErrorKind::InvalidSubcommand => (
+                "cli-error-invalid-subcommand",
+                vec![("arg", context(ContextKind::InvalidSubcommand)?)],
+            ),
+            ErrorKind::MissingRequiredArgument => (
+                "cli-error-missing-required-argument",
+                vec![("arg", context(ContextKind::InvalidArg)?)],
+            ),
+            ErrorKind::InvalidValue | ErrorKind::ValueValidation => (
+                "cli-error-invalid-value",
+                vec![
+                    ("arg", context(ContextKind::InvalidArg)?),
+                    (
+                        "value",
+                        context(ContextKind::InvalidValue).unwrap_or_default(),
+                    ),
+                ],
+            ),
+            ErrorKind::MissingSubcommand => (
+                "cli-error-missing-subcommand",
+                vec![(
+                    "arg",
+                    context(ContextKind::InvalidSubcommand).unwrap_or_default(),
+                )],
+            ),
+            ErrorKind::ArgumentConflict => (
+                "cli-error-argument-conflict",
+                vec![
+                    ("arg", context(ContextKind::InvalidArg)?),
+                    ("prior", context(ContextKind::PriorArg).unwrap_or_default()),
+                ],
+            ),
+            _ => return None,
+        };
+        let format = |msg_id: &str, params: Vec<(&str, String)>| {
+            i18n.format_msg(
+                lang_id,
+                msg_id,
+                Some(
+                    params
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value))
+                        .collect(),
+                ),
+            )
+            .ok()
+        };
+        let mut rendered = format("cli-error", vec![("message", format(msg_id, params)?)])?;
+        if let Some(suggested) = context(ContextKind::SuggestedSubcommand)
+            .or_else(|| context(ContextKind::SuggestedArg))
+            .or_else(|| context(ContextKind::SuggestedValue))
+        {
+            rendered.push_str("\n\n  ");
+            rendered.push_str(&format(
+                "cli-error-tip-similar",
+                vec![("value", suggested)],
+            )?);
+        }
+        rendered.push_str("\n\n");
+        rendered.push_str(&format("cli-error-try-help", vec![])?);
+        rendered.push('\n');
+        Some(rendered)
+    }
+}
+
+fn localize_command(
+    mut cmd: Command,
+    prefix: &str,
+    i18n: &I18N,
+    lang_id: &LanguageIdentifier,
+) -> Command {
+    let text = |msg_id: String| i18n.format_msg(lang_id, msg_id, None).ok();
+    if let Some(about) = text(format!("{}-about", prefix)) {
+        cmd = cmd.about(about);
+    }
+    if let Some(usage) = text("cli-heading-usage".to_string()) {
+        cmd = cmd.help_template(format!(
+            "{{before-help}}{{about-with-newline}}\n{} {{usage}}\n\n{{all-args}}{{after-help}}",
+            usage
+        ));
+    }
+    if let Some(heading) = text("cli-heading-commands".to_string()) {
+        cmd = cmd.subcommand_help_heading(heading);
+    }
+    let arguments_heading = text("cli-heading-arguments".to_string());
+    let options_heading = text("cli-heading-options".to_string());
+    // the generated flags can not be changed once built, so replace them
+    let flag = |id: &'static str, short: char, action: ArgAction| {
+        let arg = Arg::new(id).short(short).long(id).action(action);
+        match text(format!("cli-arg-{}", id)) {
+            Some(help) => arg.help(help),
+            None => arg,
+        }
+    };
+    cmd = cmd
+        .disable_help_flag(true)
+        .arg(flag("help", 'h', ArgAction::Help));
+    if cmd.get_version().is_some() {
+        cmd = cmd
+            .disable_version_flag(true)
+            .arg(flag("version", 'V', ArgAction::Version));
+    }
+    // positionals go first so that their section is rendered before the options
+    let arg_ids = cmd
+        .get_positionals()
+        .chain(cmd.get_arguments().filter(|arg| !arg.is_positional()))
+        .map(|arg| arg.get_id().to_string())
+        .collect::<Vec<_>>();
+    for id in arg_ids {
+        // shared arguments fall back to a key without the subcommand prefix
+        let key = id.replace('_', "-");
+        let help =
+            text(format!("{}-arg-{}", prefix, key)).or_else(|| text(format!("cli-arg-{}", key)));
+        cmd = cmd.mut_arg(&id, |mut arg| {
+            if let Some(help) = help {
+                arg = arg.help(help);
+            }
+            let heading = if arg.is_positional() {
+                arguments_heading.to_owned()
+            } else {
+                options_heading.to_owned()
+            };
+            if let (None, Some(heading)) = (arg.get_help_heading(), heading) {
+                arg = arg.help_heading(heading);
+            }
+            arg
+        });
+    }
+    let names = cmd
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect::<Vec<_>>();
+    for name in names {
+        let sub_prefix = format!("{}-{}", prefix, name);
+        cmd = cmd.mut_subcommand(&name, |sub| {
+            localize_command(sub, &sub_prefix, i18n, lang_id)
+        });
+    }
+    cmd
+}
+
+/// Localizes the `help` subcommands, which only exist after the command is built.
+fn localize_help_subcommand(
+    mut cmd: Command,
+    i18n: &I18N,
+    lang_id: &LanguageIdentifier,
+) -> Command {
+    let names = cmd
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect::<Vec<_>>();
+    for name in names {
+        cmd = cmd.mut_subcommand(&name, |sub| {
+            if name == "help" {
+                match i18n.format_msg(lang_id, "cli-help-about", None) {
+                    Ok(about) => sub.about(about),
+                    Err(_) => sub,
+                }
+            } else {
+                localize_help_subcommand(sub, i18n, lang_id)
+            }
+        });
+    }
+    cmd
+}
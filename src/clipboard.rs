@@ -0,0 +1,49 @@
+//! The system clipboard, through the copy program of the platform.
+
+use std::process::Stdio;
+
+use anyhow::anyhow;
+use tokio::io::AsyncWriteExt;
+
+/// Copy programs to try in order, the first one found is used.
+fn programs() -> Vec<(&'static str, Vec<&'static str>)> {
+    if cfg!(target_os = "windows") {
+        vec![("clip", vec![])]
+    } else if cfg!(target_os = "macos") {
+        vec![("pbcopy", vec![])]
+    } else {
+        let mut programs = vec![
+            ("xclip", vec!["-selection", "clipboard"]),
+            ("xsel", vec!["--clipboard", "--input"]),
+        ];
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            programs.insert(0, ("wl-copy", vec![]));
+        }
+        programs
+    }
+}
+
+/// Puts `text` on the clipboard.
+pub async fn copy(text: &str) -> anyhow::Result<()> {
+    for (program, args) in programs() {
+        let mut child = match tokio::process::Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("no stdin"))?;
+        stdin.write_all(text.as_bytes()).await?;
+        drop(stdin);
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(anyhow!("{} {}", program, status));
+        }
+        return Ok(());
+    }
+    Err(anyhow!("no clipboard program found"))
+}
@@ -1,6 +1,183 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::DocumentMut;
+
+use crate::git::Protocol;
+
+/// Version of the config format, configs without a version are version 0.
+pub const CONFIG_VERSION: i64 = 1;
+
+/// Upgrades a config document by one version.
+type MigrationStep = fn(&mut DocumentMut);
+
+/// Upgrades of the config format with their changelog entry, the one at index
+/// `i` upgrades version `i` to `i + 1`.
+const MIGRATIONS: &[(&str, MigrationStep)] = &[("record the config version", |_| {})];
+
 #[derive(serde::Deserialize, serde::Serialize, Default)]
 pub struct Config {
+    pub version: Option<i64>,
     pub repo_dir: Option<String>,
     pub open_with: Option<String>,
     pub config_editor: Option<String>,
+    pub theme: Option<ThemeConfig>,
+    pub table_style: Option<TableStyle>,
+    pub update_url: Option<String>,
+    pub update_check: Option<bool>,
+    pub aliases: Option<BTreeMap<String, String>>,
+    pub protocol: Option<Protocol>,
+    pub hosts: Option<BTreeMap<String, HostConfig>>,
+    pub openers: Option<BTreeMap<String, OpenerConfig>>,
+    pub remember_opener: Option<bool>,
+    pub enter: Option<String>,
+    pub owners: Option<BTreeMap<String, OwnerConfig>>,
+    pub roots: Option<BTreeMap<String, String>>,
+    pub default_host: Option<String>,
+    /// Seconds after which a git process is killed.
+    pub timeout: Option<u64>,
+    /// Requests to forges in flight at once.
+    pub api_parallel: Option<usize>,
+    /// Seconds to wait at most for the rate limit of a forge to be reset.
+    pub api_max_wait: Option<u64>,
+    pub hooks: Option<HooksConfig>,
+    pub groups: Option<BTreeMap<String, GroupConfig>>,
+    pub jobs: Option<BTreeMap<String, JobConfig>>,
+    pub templates: Option<BTreeMap<String, TemplateConfig>>,
+}
+
+/// A named program to open repositories with, `{path}` in the arguments is
+/// replaced with the repository, which is appended otherwise.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct OpenerConfig {
+    pub cmd: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Settings for the repositories of an owner, the path without its last
+/// component.
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
+pub struct OwnerConfig {
+    pub enter: Option<String>,
+}
+
+/// Commands run in repositories after they were made, shell commands or
+/// paths of rhai scripts ending in `.rhai`.
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
+pub struct HooksConfig {
+    pub post_clone: Option<String>,
+    pub post_create: Option<String>,
+}
+
+/// A named set of repositories, the ones its targets stand for and, if any
+/// filter is set, the ones matching all filters.
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
+pub struct GroupConfig {
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    pub hostname: Option<String>,
+    pub owner: Option<String>,
+    pub user: Option<String>,
+    pub path: Option<String>,
+    pub devcontainer: Option<bool>,
+    pub lang: Option<String>,
+    pub script: Option<String>,
+    /// An expression like those of `--where`.
+    #[serde(rename = "where")]
+    pub filter_where: Option<String>,
+}
+
+/// A recurring operation, run by `jobs run` from a scheduler like cron.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct JobConfig {
+    /// Arguments of rerman, like an alias.
+    pub command: String,
+    /// When the job is meant to run, only shown by `jobs list`.
+    pub schedule: Option<String>,
+}
+
+/// A project template for `create --template`, `templates/<name>` next to
+/// the config if it has neither a path nor a url.
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
+pub struct TemplateConfig {
+    pub path: Option<String>,
+    /// Git repository of the template, cloned into the data directory.
+    pub url: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
+pub struct HostConfig {
+    pub protocol: Option<Protocol>,
+    /// `github`, `gitlab`, `gitea` or a forge of a plugin, guessed for well
+    /// known hostnames.
+    pub forge: Option<String>,
+    /// Environment variable with an access token for the forge, by default
+    /// `GITHUB_TOKEN`, `GITLAB_TOKEN` or `GITEA_TOKEN`.
+    pub token_env: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    #[default]
+    Ascii,
+    Rounded,
+    Markdown,
+    Borderless,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
+pub struct ThemeConfig {
+    pub error: Option<String>,
+    pub info: Option<String>,
+    pub header: Option<String>,
+    pub dirty: Option<String>,
+    pub clean: Option<String>,
+}
+
+/// The migrations applied to a config.
+pub struct Migration {
+    pub from: i64,
+    pub changes: Vec<&'static str>,
+}
+
+/// Upgrades `config` to the current version, keeping comments and layout.
+/// Returns `None` if it is current already, newer, or not valid TOML.
+pub fn migrate(config: &str) -> Option<(String, Migration)> {
+    let mut document = config.parse::<DocumentMut>().ok()?;
+    let from = document
+        .get("version")
+        .and_then(|version| version.as_integer())
+        .unwrap_or(0);
+    if from >= CONFIG_VERSION {
+        return None;
+    }
+    let mut changes = vec![];
+    for (change, migration) in MIGRATIONS.iter().skip(from.max(0) as usize) {
+        migration(&mut document);
+        changes.push(*change);
+    }
+    document["version"] = toml_edit::value(CONFIG_VERSION);
+    Some((document.to_string(), Migration { from, changes }))
+}
+
+/// Writes the migrated config to `file`, backing the previous one up next to
+/// it. Returns the backup file.
+pub async fn save_migrated(
+    file: &Path,
+    previous: &str,
+    migrated: &str,
+    migration: &Migration,
+) -> anyhow::Result<PathBuf> {
+    let mut backup_file = file.to_path_buf().into_os_string();
+    backup_file.push(format!(".v{}.bak", migration.from));
+    let backup_file = PathBuf::from(backup_file);
+    tokio::fs::write(&backup_file, previous).await?;
+    tokio::fs::write(file, migrated).await?;
+    Ok(backup_file)
 }
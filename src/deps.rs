@@ -0,0 +1,119 @@
+//! Dependencies locked in the lockfiles of repositories, for `audit deps`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Names of the lockfiles read, of cargo, npm and go.
+pub const LOCKFILES: &[&str] = &["Cargo.lock", "package-lock.json", "go.sum"];
+
+/// A package at a version, as locked.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dependency {
+    pub ecosystem: &'static str,
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(serde::Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoPackage>,
+}
+
+#[derive(serde::Deserialize)]
+struct CargoPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct NpmLock {
+    /// Packages by their path, in lockfiles of version 2 and later.
+    packages: Option<BTreeMap<String, NpmPackage>>,
+    /// Nested packages by name, in lockfiles of version 1.
+    #[serde(default)]
+    dependencies: BTreeMap<String, NpmPackage>,
+}
+
+#[derive(serde::Deserialize)]
+struct NpmPackage {
+    version: Option<String>,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dependencies: BTreeMap<String, NpmPackage>,
+}
+
+/// The dependencies locked in `text`, the contents of the lockfile named
+/// `file`, without the packages of the repository itself. Lockfiles of other
+/// names have none.
+pub fn parse(file: &str, text: &str) -> anyhow::Result<BTreeSet<Dependency>> {
+    let mut deps = BTreeSet::new();
+    let dep = |ecosystem, name: &str, version: &str| Dependency {
+        ecosystem,
+        name: name.to_string(),
+        version: version.to_string(),
+    };
+    match file {
+        "Cargo.lock" => {
+            let lock: CargoLock = toml::from_str(text)?;
+            // crates of the workspace and its path dependencies have no source
+            deps.extend(
+                lock.package
+                    .iter()
+                    .filter(|package| package.source.is_some())
+                    .map(|package| dep("cargo", &package.name, &package.version)),
+            );
+        }
+        "package-lock.json" => {
+            let lock: NpmLock = serde_json::from_str(text)?;
+            match lock.packages {
+                Some(packages) => {
+                    for (path, package) in packages {
+                        // the root and workspaces are not under node_modules
+                        let (Some((_, name)), Some(version)) =
+                            (path.rsplit_once("node_modules/"), &package.version)
+                        else {
+                            continue;
+                        };
+                        if !package.link {
+                            deps.insert(dep("npm", name, version));
+                        }
+                    }
+                }
+                None => {
+                    let mut nested = vec![&lock.dependencies];
+                    while let Some(packages) = nested.pop() {
+                        for (name, package) in packages {
+                            if let Some(version) = &package.version {
+                                deps.insert(dep("npm", name, version));
+                            }
+                            nested.push(&package.dependencies);
+                        }
+                    }
+                }
+            }
+        }
+        "go.sum" => {
+            for line in text.lines() {
+                let mut fields = line.split_whitespace();
+                let (Some(module), Some(version)) = (fields.next(), fields.next()) else {
+                    continue;
+                };
+                let version = version.strip_suffix("/go.mod").unwrap_or(version);
+                deps.insert(dep("go", module, version));
+            }
+        }
+        _ => {}
+    }
+    Ok(deps)
+}
+
+/// Whether `name` is the package `query`, ignoring case. Go modules and
+/// scoped npm packages also match by their last part, so `zap` matches
+/// `go.uber.org/zap`.
+pub fn matches(name: &str, query: &str) -> bool {
+    let name = name.to_lowercase();
+    let query = query.to_lowercase();
+    name == query || name.ends_with(&format!("/{}", query))
+}
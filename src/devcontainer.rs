@@ -0,0 +1,49 @@
+//! Detection and launching of [devcontainers](https://containers.dev).
+
+use std::path::Path;
+
+/// Whether `dir` has a devcontainer configuration.
+pub fn detect(dir: impl AsRef<Path>) -> bool {
+    let dir = dir.as_ref();
+    dir.join(".devcontainer").is_dir() || dir.join(".devcontainer.json").is_file()
+}
+
+/// The VS Code folder uri opening `dir` in its devcontainer.
+pub fn folder_uri(dir: impl AsRef<Path>) -> String {
+    let dir = dir.as_ref();
+    let hex = dir
+        .to_string_lossy()
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("vscode-remote://dev-container+{}/workspaces/{}", hex, name)
+}
+
+/// Opens `dir` with the devcontainer CLI, or with VS Code if the CLI is not
+/// installed.
+pub async fn open(dir: impl AsRef<Path>) -> anyhow::Result<()> {
+    let dir = dir.as_ref();
+    let status = match tokio::process::Command::new("devcontainer")
+        .arg("open")
+        .arg(dir)
+        .status()
+        .await
+    {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            tokio::process::Command::new("code")
+                .arg("--folder-uri")
+                .arg(folder_uri(dir))
+                .status()
+                .await?
+        }
+        status => status?,
+    };
+    if !status.success() {
+        anyhow::bail!("devcontainer {}", status);
+    }
+    Ok(())
+}
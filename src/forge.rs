@@ -0,0 +1,378 @@
+//! Web APIs of the forges hosting repositories.
+
+use crate::http::ApiClient;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+/// What a forge knows about a repository.
+#[derive(Default, serde::Deserialize)]
+pub struct RepoInfo {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+/// A repository listed by a forge, like those starred by a user.
+pub struct ForgeRepo {
+    /// The `owner/name` path of the repository.
+    pub project: String,
+    pub clone_url: String,
+    pub description: Option<String>,
+}
+
+/// A pull request to open, of `branch` into `base`.
+pub struct PullRequest<'a> {
+    pub branch: &'a str,
+    pub base: &'a str,
+    pub title: &'a str,
+}
+
+/// A page of a repository on its forge.
+pub enum WebPage<'a> {
+    Repo,
+    Commit(&'a str),
+    PullRequest(u64),
+    /// A file at a commit, with an optional line to jump to.
+    File {
+        commit: &'a str,
+        path: &'a str,
+        line: Option<u32>,
+    },
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubRepo {
+    description: Option<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubListedRepo {
+    full_name: String,
+    clone_url: String,
+    description: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabListedProject {
+    path_with_namespace: String,
+    http_url_to_repo: String,
+    description: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubUser {
+    login: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabUser {
+    username: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabNamespace {
+    id: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct GitHubPullRequest {
+    html_url: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabMergeRequest {
+    web_url: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GitLabProject {
+    description: Option<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+impl Forge {
+    /// Guesses the forge from well known hostnames.
+    pub fn detect(host: &str) -> Option<Self> {
+        match host {
+            "github.com" => Some(Forge::GitHub),
+            "gitlab.com" => Some(Forge::GitLab),
+            "codeberg.org" | "gitea.com" => Some(Forge::Gitea),
+            _ => None,
+        }
+    }
+
+    /// The forge of a name in the host config, `None` for the names of forges
+    /// provided by plugins.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "github" => Some(Forge::GitHub),
+            "gitlab" => Some(Forge::GitLab),
+            "gitea" => Some(Forge::Gitea),
+            _ => None,
+        }
+    }
+
+    /// Fetches the description and topics of the repository `project`,
+    /// an `owner/name` path on `host`.
+    pub async fn repo_info(
+        &self,
+        api: &ApiClient,
+        host: &str,
+        project: &str,
+    ) -> anyhow::Result<RepoInfo> {
+        match self {
+            Forge::GitHub | Forge::Gitea => {
+                let repo: GitHubRepo = api
+                    .json(api.get(format!("{}/repos/{}", self.api(host), project)))
+                    .await?;
+                Ok(RepoInfo {
+                    description: repo.description.filter(|d| !d.is_empty()),
+                    topics: repo.topics,
+                })
+            }
+            Forge::GitLab => {
+                let project =
+                    url::form_urlencoded::byte_serialize(project.as_bytes()).collect::<String>();
+                let repo: GitLabProject = api
+                    .json(api.get(format!("{}/projects/{}", self.api(host), project)))
+                    .await?;
+                Ok(RepoInfo {
+                    description: repo.description.filter(|d| !d.is_empty()),
+                    topics: repo.topics,
+                })
+            }
+        }
+    }
+
+    /// The base url of the web API on `host`, GitHub Enterprise for github
+    /// hosts other than github.com.
+    fn api(&self, host: &str) -> String {
+        match self {
+            Forge::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+            Forge::GitHub => format!("https://{}/api/v3", host),
+            Forge::GitLab => format!("https://{}/api/v4", host),
+            Forge::Gitea => format!("https://{}/api/v1", host),
+        }
+    }
+
+    /// The environment variable holding an access token by default.
+    pub fn token_env(&self) -> &'static str {
+        match self {
+            Forge::GitHub => "GITHUB_TOKEN",
+            Forge::GitLab => "GITLAB_TOKEN",
+            Forge::Gitea => "GITEA_TOKEN",
+        }
+    }
+
+    /// The name of the user `token` belongs to.
+    pub async fn login(&self, api: &ApiClient, host: &str, token: &str) -> anyhow::Result<String> {
+        let request = || {
+            api.get(format!("{}/user", self.api(host)))
+                .bearer_auth(token)
+        };
+        Ok(match self {
+            Forge::GitHub | Forge::Gitea => api.json::<GitHubUser>(request()).await?.login,
+            Forge::GitLab => api.json::<GitLabUser>(request()).await?.username,
+        })
+    }
+
+    /// The repositories on `host` the owner of `token` starred, or watches
+    /// with `watched`, which GitLab does not list.
+    pub async fn starred(
+        &self,
+        api: &ApiClient,
+        host: &str,
+        token: &str,
+        watched: bool,
+    ) -> anyhow::Result<Vec<ForgeRepo>> {
+        const PER_PAGE: usize = 50;
+        let base = self.api(host);
+        let mut repos = vec![];
+        for page in 1.. {
+            let request = match (self, watched) {
+                (Forge::GitHub | Forge::Gitea, _) => api
+                    .get(format!(
+                        "{}/user/{}",
+                        base,
+                        if watched { "subscriptions" } else { "starred" }
+                    ))
+                    .query(&[("per_page", PER_PAGE), ("limit", PER_PAGE), ("page", page)]),
+                (Forge::GitLab, false) => api
+                    .get(format!("{}/projects", base))
+                    .query(&[("starred", "true")])
+                    .query(&[("per_page", PER_PAGE), ("page", page)]),
+                (Forge::GitLab, true) => {
+                    anyhow::bail!("GitLab does not list watched projects")
+                }
+            };
+            let request = request.bearer_auth(token);
+            let listed = match self {
+                Forge::GitHub | Forge::Gitea => api
+                    .json::<Vec<GitHubListedRepo>>(request)
+                    .await?
+                    .into_iter()
+                    .map(|repo| ForgeRepo {
+                        project: repo.full_name,
+                        clone_url: repo.clone_url,
+                        description: repo.description.filter(|d| !d.is_empty()),
+                    })
+                    .collect::<Vec<_>>(),
+                Forge::GitLab => api
+                    .json::<Vec<GitLabListedProject>>(request)
+                    .await?
+                    .into_iter()
+                    .map(|project| ForgeRepo {
+                        project: project.path_with_namespace,
+                        clone_url: project.http_url_to_repo,
+                        description: project.description.filter(|d| !d.is_empty()),
+                    })
+                    .collect(),
+            };
+            let last = listed.len() < PER_PAGE;
+            repos.extend(listed);
+            if last {
+                break;
+            }
+        }
+        Ok(repos)
+    }
+
+    /// Creates the private repository `name` of `owner` on `host`, a user or
+    /// an organization the owner of `token` belongs to.
+    pub async fn create_repo(
+        &self,
+        api: &ApiClient,
+        host: &str,
+        owner: &str,
+        name: &str,
+        token: &str,
+    ) -> anyhow::Result<()> {
+        let base = self.api(host);
+        match self {
+            Forge::GitHub | Forge::Gitea => {
+                let url = if self.login(api, host, token).await? == owner {
+                    format!("{}/user/repos", base)
+                } else {
+                    format!("{}/orgs/{}/repos", base, owner)
+                };
+                api.send(
+                    api.post(url)
+                        .bearer_auth(token)
+                        .json(&serde_json::json!({ "name": name, "private": true })),
+                )
+                .await?;
+            }
+            Forge::GitLab => {
+                let owner =
+                    url::form_urlencoded::byte_serialize(owner.as_bytes()).collect::<String>();
+                let namespace: GitLabNamespace = api
+                    .json(
+                        api.get(format!("{}/namespaces/{}", base, owner))
+                            .bearer_auth(token),
+                    )
+                    .await?;
+                api.send(
+                    api.post(format!("{}/projects", base))
+                        .bearer_auth(token)
+                        .json(&serde_json::json!({
+                            "path": name,
+                            "namespace_id": namespace.id,
+                            "visibility": "private",
+                        })),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens `pull` in the repository `project` on `host`, returning its url.
+    pub async fn open_pull_request(
+        &self,
+        api: &ApiClient,
+        host: &str,
+        project: &str,
+        token: &str,
+        pull: &PullRequest<'_>,
+    ) -> anyhow::Result<String> {
+        match self {
+            Forge::GitHub | Forge::Gitea => {
+                let pull: GitHubPullRequest = api
+                    .json(
+                        api.post(format!("{}/repos/{}/pulls", self.api(host), project))
+                            .bearer_auth(token)
+                            .json(&serde_json::json!({
+                                "title": pull.title,
+                                "head": pull.branch,
+                                "base": pull.base,
+                            })),
+                    )
+                    .await?;
+                Ok(pull.html_url)
+            }
+            Forge::GitLab => {
+                let project =
+                    url::form_urlencoded::byte_serialize(project.as_bytes()).collect::<String>();
+                let merge: GitLabMergeRequest = api
+                    .json(
+                        api.post(format!(
+                            "{}/projects/{}/merge_requests",
+                            self.api(host),
+                            project
+                        ))
+                        .bearer_auth(token)
+                        .json(&serde_json::json!({
+                            "title": pull.title,
+                            "source_branch": pull.branch,
+                            "target_branch": pull.base,
+                        })),
+                    )
+                    .await?;
+                Ok(merge.web_url)
+            }
+        }
+    }
+
+    /// The url of `page` of the repository `project` on `host`.
+    pub fn web_url(&self, host: &str, project: &str, page: &WebPage) -> String {
+        let repo = format!("https://{}/{}", host, project);
+        // gitlab keeps the pages of a project below `/-/`
+        let prefix = match self {
+            Forge::GitLab => "/-",
+            Forge::GitHub | Forge::Gitea => "",
+        };
+        match page {
+            WebPage::Repo => repo,
+            WebPage::Commit(sha) => format!("{}{}/commit/{}", repo, prefix, sha),
+            WebPage::PullRequest(number) => {
+                let pulls = match self {
+                    Forge::GitHub => "pull",
+                    Forge::GitLab => "merge_requests",
+                    Forge::Gitea => "pulls",
+                };
+                format!("{}{}/{}/{}", repo, prefix, pulls, number)
+            }
+            WebPage::File { commit, path, line } => {
+                let path = path.trim_start_matches("./").trim_start_matches('/');
+                let mut url = match self {
+                    Forge::Gitea => format!("{}/src/commit/{}/{}", repo, commit, path),
+                    Forge::GitHub | Forge::GitLab => {
+                        format!("{}{}/blob/{}/{}", repo, prefix, commit, path)
+                    }
+                };
+                if let Some(line) = line {
+                    url += &format!("#L{}", line);
+                }
+                url
+            }
+        }
+    }
+}
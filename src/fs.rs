@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+
+/// Moves the directory `from` to `to`, creating the parents of `to` and
+/// falling back to copying when both are on different filesystems.
+pub async fn move_dir(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if tokio::fs::try_exists(to).await? {
+        return Err(anyhow!("destination already exists: {}", to.display()));
+    }
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    match tokio::fs::rename(from, to).await {
+        Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {
+            let (source, dest) = (from.to_path_buf(), to.to_path_buf());
+            tokio::task::spawn_blocking(move || copy_dir(&source, &dest)).await??;
+            tokio::fs::remove_dir_all(from).await?;
+            Ok(())
+        }
+        result => Ok(result?),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let dest = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &dest)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(std::fs::read_link(entry.path())?, &dest)?;
+            #[cfg(not(unix))]
+            std::fs::copy(entry.path(), &dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// The total size of the files below `dir` in bytes, not following symlinks
+/// and skipping what cannot be read.
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => {
+                entry.metadata().map(|meta| meta.len()).unwrap_or_default()
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+/// The files below `dir` relative to it, not following symlinks, leaving
+/// out `.git` and what cannot be read.
+pub fn files(dir: &Path) -> Vec<PathBuf> {
+    fn walk(base: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() && entry.file_name() != ".git" => {
+                    walk(base, &entry.path(), files)
+                }
+                Ok(file_type) if file_type.is_file() => {
+                    if let Ok(file) = entry.path().strip_prefix(base) {
+                        files.push(file.to_path_buf());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut files = vec![];
+    walk(dir, dir, &mut files);
+    files.sort();
+    files
+}
+
+/// Whether files can be created in `path`, or in its closest existing
+/// ancestor if it does not exist yet.
+pub fn is_writable(path: &Path) -> bool {
+    match path.ancestors().find(|dir| dir.exists()) {
+        Some(dir) => tempfile::tempfile_in(dir).is_ok(),
+        None => false,
+    }
+}
+
+/// A directory being created, removed when dropped before [`PartialDir::keep`]
+/// so that cancelled operations leave nothing half done behind.
+pub struct PartialDir {
+    path: Option<PathBuf>,
+}
+
+impl PartialDir {
+    /// Guards `path` with its parents that do not exist yet, unless it exists
+    /// already.
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path
+                .ancestors()
+                .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+                .last()
+                .map(Path::to_path_buf),
+        }
+    }
+
+    pub fn keep(mut self) {
+        self.path = None;
+    }
+}
+
+impl Drop for PartialDir {
+    fn drop(&mut self) {
+        if let Some(ref path) = self.path {
+            let _ = std::fs::remove_dir_all(path);
+        }
+    }
+}
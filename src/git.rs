@@ -1,6 +1,8 @@
 use std::{
+    future::Future,
     path::{Path, PathBuf},
     process::Stdio,
+    time::Duration,
 };
 
 use anyhow::anyhow;
@@ -8,74 +10,297 @@ use lazy_regex::regex_captures;
 use tokio::process::Command;
 use url::Url;
 
-pub async fn filter_git_paths_recursively(base: impl AsRef<Path>) -> anyhow::Result<Vec<PathBuf>> {
-    let mut entries = tokio::fs::read_dir(base.as_ref()).await?;
-    let mut paths = vec![];
-    loop {
-        match entries.next_entry().await {
-            Err(err) => {
-                return Err(anyhow::Error::from(err));
-            }
-            Ok(None) => break,
-            Ok(Some(entry)) => {
-                if entry.file_type().await?.is_dir() {
-                    let entry_path = entry.path();
-                    let git_path = entry_path.join(".git");
-                    if tokio::fs::try_exists(git_path).await? {
-                        paths.push(entry_path);
-                    } else {
-                        paths
-                            .append(&mut Box::pin(filter_git_paths_recursively(entry_path)).await?);
-                    }
-                }
-            }
-        }
-    }
-    Ok(paths)
-}
-
+/// Runs git, killing it when it takes longer than the timeout, or when the
+/// future waiting for it is dropped, like on Ctrl-C.
 pub struct Git {
     exe: String,
+    timeout: Option<Duration>,
+}
+
+pub struct GitStatus {
+    pub branch: String,
+    pub dirty: bool,
 }
 
 impl Default for Git {
     fn default() -> Self {
         Self {
             exe: "git".to_string(),
+            timeout: None,
         }
     }
 }
 
 impl Git {
+    pub fn with_timeout(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            ..Self::default()
+        }
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.exe);
+        command.kill_on_drop(true);
+        command
+    }
+
+    async fn timed<T>(
+        &self,
+        args: &[&str],
+        future: impl Future<Output = std::io::Result<T>>,
+    ) -> anyhow::Result<T> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, future).await.map_err(|_| {
+                anyhow!(
+                    "git {} timed out after {}s",
+                    args.join(" "),
+                    timeout.as_secs()
+                )
+            })?,
+            None => future.await,
+        }
+        .map_err(anyhow::Error::new)
+    }
+
     pub async fn clone(
         &self,
+        options: &[&str],
         target: impl AsRef<str>,
         path: impl AsRef<str>,
     ) -> anyhow::Result<std::process::ExitStatus> {
-        Command::new(&self.exe)
+        let mut child = self
+            .command()
             .arg("clone")
+            .args(options)
             .arg("--")
             .arg(target.as_ref())
             .arg(path.as_ref())
             .stdout(Stdio::inherit())
-            .spawn()?
-            .wait()
+            .spawn()?;
+        self.timed(&["clone", target.as_ref()], child.wait()).await
+    }
+
+    /// Runs git with its stdout discarded, returns the exit status and stderr.
+    pub async fn run_captured(
+        &self,
+        args: &[&str],
+    ) -> anyhow::Result<(std::process::ExitStatus, String)> {
+        let output = self
+            .timed(
+                args,
+                self.command()
+                    .args(args)
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::piped())
+                    .output(),
+            )
+            .await?;
+        Ok((
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ))
+    }
+
+    /// Runs git in `dir` and returns its stdout, fails if git exits unsuccessfully.
+    pub async fn output(&self, dir: impl AsRef<Path>, args: &[&str]) -> anyhow::Result<String> {
+        Ok(self.output_with_stderr(dir, args).await?.0)
+    }
+
+    /// Like [`Git::output`], also returning stderr for the warnings of git.
+    pub async fn output_with_stderr(
+        &self,
+        dir: impl AsRef<Path>,
+        args: &[&str],
+    ) -> anyhow::Result<(String, String)> {
+        let output = self
+            .timed(
+                args,
+                self.command()
+                    .arg("-C")
+                    .arg(dir.as_ref())
+                    .args(args)
+                    .output(),
+            )
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok((
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ))
+    }
+
+    pub async fn remotes(&self, dir: impl AsRef<Path>) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .output(dir, &["remote"])
+            .await?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Names and fetch urls of all remotes.
+    pub async fn remote_urls(
+        &self,
+        dir: impl AsRef<Path>,
+    ) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(self
+            .output(dir, &["remote", "-v"])
+            .await?
+            .lines()
+            .filter_map(|line| line.strip_suffix(" (fetch)")?.split_once('\t'))
+            .map(|(name, url)| (name.to_string(), url.to_string()))
+            .collect())
+    }
+
+    /// The default branch of `remote`, asking the remote if it is not known yet.
+    pub async fn default_branch(
+        &self,
+        dir: impl AsRef<Path>,
+        remote: &str,
+    ) -> anyhow::Result<String> {
+        let head = format!("refs/remotes/{}/HEAD", remote);
+        let branch = match self.output(&dir, &["symbolic-ref", "--short", &head]).await {
+            Ok(branch) => branch,
+            Err(_) => {
+                self.output(&dir, &["remote", "set-head", remote, "--auto"])
+                    .await?;
+                self.output(&dir, &["symbolic-ref", "--short", &head])
+                    .await?
+            }
+        };
+        let branch = branch.trim();
+        Ok(branch
+            .strip_prefix(&format!("{}/", remote))
+            .unwrap_or(branch)
+            .to_string())
+    }
+
+    /// The commit checked out in `dir`, `None` on an unborn branch.
+    pub async fn head(&self, dir: impl AsRef<Path>) -> Option<String> {
+        self.output(dir, &["rev-parse", "--verify", "--quiet", "HEAD"])
             .await
-            .map_err(anyhow::Error::new)
+            .ok()
+    }
+
+    pub async fn current_branch(&self, dir: impl AsRef<Path>) -> anyhow::Result<String> {
+        Ok(self
+            .output(dir, &["symbolic-ref", "--short", "HEAD"])
+            .await?
+            .trim()
+            .to_string())
+    }
+
+    pub async fn remote_url(&self, dir: impl AsRef<Path>, remote: &str) -> anyhow::Result<String> {
+        Ok(self
+            .output(dir, &["remote", "get-url", remote])
+            .await?
+            .trim()
+            .to_string())
+    }
+
+    pub async fn status(&self, dir: impl AsRef<Path>) -> anyhow::Result<GitStatus> {
+        let output = self
+            .output(dir, &["status", "--porcelain=v1", "--branch"])
+            .await?;
+        let mut lines = output.lines();
+        let branch = lines
+            .next()
+            .and_then(|line| line.strip_prefix("## "))
+            .map(|line| {
+                let line = line.strip_prefix("No commits yet on ").unwrap_or(line);
+                line.split("...")
+                    .next()
+                    .unwrap_or(line)
+                    .split(' ')
+                    .next()
+                    .unwrap_or(line)
+                    .to_string()
+            })
+            .unwrap_or_default();
+        Ok(GitStatus {
+            branch,
+            dirty: lines.next().is_some(),
+        })
+    }
+
+    /// Serves the repositories below `base_path` read-only over the git
+    /// protocol until killed.
+    pub async fn daemon(
+        &self,
+        base_path: &Path,
+        port: u16,
+    ) -> anyhow::Result<std::process::ExitStatus> {
+        Ok(self
+            .command()
+            .arg("daemon")
+            .arg("--reuseaddr")
+            .arg("--export-all")
+            .arg(format!("--base-path={}", base_path.display()))
+            .arg(format!("--port={}", port))
+            .arg(base_path)
+            .status()
+            .await?)
     }
 
     pub async fn init(&self, path: impl AsRef<str>) -> anyhow::Result<std::process::ExitStatus> {
-        Command::new(&self.exe)
+        let mut child = self
+            .command()
             .arg("init")
             .arg(path.as_ref())
             .stdout(Stdio::inherit())
-            .spawn()?
-            .wait()
-            .await
-            .map_err(anyhow::Error::new)
+            .spawn()?;
+        self.timed(&["init"], child.wait()).await
     }
 }
 
+/// The file of the repository in `dir` naming the checked out branch.
+pub fn head_file(dir: &Path) -> PathBuf {
+    dir.join(".git").join("HEAD")
+}
+
+/// The checked out branch of the repository in `dir`, read from its HEAD
+/// without running git. `None` when detached or not a git repository.
+pub fn head_branch(dir: &Path) -> Option<String> {
+    std::fs::read_to_string(head_file(dir))
+        .ok()?
+        .trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
+}
+
+/// The new url of a remote that moved, from the redirect warning of git or
+/// the "repository moved" message of the forge in `stderr`.
+pub fn moved_url(stderr: &str) -> Option<String> {
+    let mut lines = stderr.lines();
+    while let Some(line) = lines.next() {
+        if let Some(url) = line.trim().strip_prefix("warning: redirecting to ") {
+            return Some(url.trim().trim_end_matches('/').to_string());
+        }
+        if line.contains("This repository moved") {
+            return lines
+                .next()
+                .map(|line| line.trim().trim_start_matches("remote:").trim().to_string())
+                .filter(|url| !url.is_empty());
+        }
+    }
+    None
+}
+
+/// Protocol used to clone from hosts of shorthand targets.
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Ssh,
+    Https,
+}
+
 #[allow(dead_code)]
 pub enum GitUrl {
     Ssh {
@@ -201,6 +426,37 @@ impl GitUrl {
         }
     }
 
+    /// Parses a shorthand target like `github.com/owner/repo`, a url without
+    /// scheme whose first segment is a hostname.
+    pub fn parse_shorthand(target: impl AsRef<str>) -> Option<Self> {
+        let target = target.as_ref();
+        if target.contains(':') || Path::new(target).exists() {
+            return None;
+        }
+        let (host, path) = target.split_once('/')?;
+        if !host.contains('.') || !path.contains('/') {
+            return None;
+        }
+        Some(GitUrl::Http {
+            https: true,
+            host: host.to_string(),
+            port: None,
+            path: format!("/{}", path),
+        })
+    }
+
+    /// The url of the same repository in `protocol`, `None` for local urls.
+    pub fn to_protocol(&self, protocol: Protocol) -> Option<String> {
+        if let GitUrl::File { .. } = self {
+            return None;
+        }
+        let project = self.project_path();
+        Some(match protocol {
+            Protocol::Ssh => format!("git@{}:{}.git", self.host(), project),
+            Protocol::Https => format!("https://{}/{}.git", self.host(), project),
+        })
+    }
+
     pub fn username(&self) -> String {
         match self {
             GitUrl::Ssh { username, .. } => username.to_owned().unwrap_or_default(),
@@ -221,6 +477,32 @@ impl GitUrl {
         }
     }
 
+    /// The `owner/name` path of the repository, without slashes around it or
+    /// a `.git` suffix.
+    pub fn project_path(&self) -> String {
+        let path = self.path().trim_matches('/');
+        let path = path
+            .strip_suffix(".git")
+            .unwrap_or(path)
+            .trim_end_matches('/');
+        match self.username().as_str() {
+            "" => path.to_string(),
+            username => format!("{}/{}", username, path),
+        }
+    }
+
+    /// The hostname, owner and name the layout places the repository at,
+    /// the same for each protocol a repository is cloned with.
+    pub fn canonical(&self) -> (String, String, String) {
+        let project = self.project_path();
+        let (owner, name) = crate::layout::split_path(&project);
+        (
+            self.host().to_lowercase(),
+            owner.to_string(),
+            name.to_string(),
+        )
+    }
+
     pub fn path(&self) -> &str {
         match self {
             GitUrl::Ssh { path, .. } => path,
@@ -0,0 +1,211 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+use log::debug;
+use reqwest::{
+    header::{HeaderMap, AUTHORIZATION, ETAG, IF_NONE_MATCH, RETRY_AFTER},
+    Method, RequestBuilder, StatusCode,
+};
+use sha2::{Digest, Sha256};
+use tokio::sync::Semaphore;
+
+pub fn client() -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent(concat!("rerman/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+pub async fn download(url: impl AsRef<str>) -> anyhow::Result<Vec<u8>> {
+    Ok(client()?
+        .get(url.as_ref())
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Client of the web APIs of forges. GET responses are cached in a
+/// directory and revalidated with their ETag, which forges like GitHub do not
+/// count against the rate limit. When the rate limit of a host is used up,
+/// requests wait until it is reset, or fail if that takes too long.
+pub struct ApiClient {
+    client: reqwest::Client,
+    cache_dir: PathBuf,
+    /// Limits the requests in flight at once.
+    permits: Semaphore,
+    max_wait: Duration,
+    /// When the rate limits that were used up are reset, by host.
+    resets: Mutex<BTreeMap<String, SystemTime>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct CachedResponse {
+    etag: String,
+    body: String,
+}
+
+impl ApiClient {
+    pub fn new(cache_dir: PathBuf, parallel: usize, max_wait: Duration) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: client()?,
+            cache_dir,
+            permits: Semaphore::new(parallel.max(1)),
+            max_wait,
+            resets: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn get(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.client.get(url.as_ref())
+    }
+
+    pub fn post(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.client.post(url.as_ref())
+    }
+
+    /// Sends `request` and parses the json it returns.
+    pub async fn json<T: serde::de::DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> anyhow::Result<T> {
+        Ok(serde_json::from_slice(&self.send(request).await?)?)
+    }
+
+    /// Sends `request`, returning the body of its successful response.
+    pub async fn send(&self, request: RequestBuilder) -> anyhow::Result<Vec<u8>> {
+        let request = request.build()?;
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        // responses differ by token, so it is part of the key
+        let cache_file = (request.method() == Method::GET).then(|| {
+            let auth = request
+                .headers()
+                .get(AUTHORIZATION)
+                .map(|value| value.as_bytes().to_vec())
+                .unwrap_or_default();
+            let key = sha256_hex(&[request.url().as_str().as_bytes(), b"\n", &auth].concat());
+            self.cache_dir.join(format!("{}.json", key))
+        });
+        let cached = match cache_file {
+            Some(ref file) => tokio::fs::read_to_string(file)
+                .await
+                .ok()
+                .and_then(|cached| serde_json::from_str::<CachedResponse>(&cached).ok()),
+            None => None,
+        };
+        // limited responses are retried after the reset, a few times at most
+        // in case the clocks disagree on when that is
+        for _ in 0..3 {
+            self.wait_for_reset(&host).await?;
+            let mut attempt = request
+                .try_clone()
+                .ok_or_else(|| anyhow!("the request to {} can not be repeated", host))?;
+            if let Some(ref cached) = cached {
+                attempt
+                    .headers_mut()
+                    .insert(IF_NONE_MATCH, cached.etag.parse()?);
+            }
+            let _permit = self.permits.acquire().await?;
+            let response = self.client.execute(attempt).await?;
+            let status = response.status();
+            let reset = rate_limit_reset(status, response.headers());
+            if let Some(reset) = reset {
+                self.resets
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .insert(host.to_owned(), reset);
+                if status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS {
+                    continue;
+                }
+            }
+            if status == StatusCode::NOT_MODIFIED {
+                if let Some(cached) = cached {
+                    return Ok(cached.body.into_bytes());
+                }
+            }
+            let response = response.error_for_status()?;
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|etag| etag.to_str().ok())
+                .map(str::to_string);
+            let body = response.bytes().await?.to_vec();
+            if let (Some(file), Some(etag)) = (cache_file, etag) {
+                if let Ok(body) = String::from_utf8(body.clone()) {
+                    // the cache only saves requests, failing to write it is no error
+                    if let Err(err) = self.save_cached(&file, etag, body).await {
+                        debug!("could not cache the response: {:?}", err);
+                    }
+                }
+            }
+            return Ok(body);
+        }
+        anyhow::bail!("the API rate limit of {} is still used up", host)
+    }
+
+    async fn save_cached(&self, file: &Path, etag: String, body: String) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.cache_dir).await?;
+        tokio::fs::write(file, serde_json::to_string(&CachedResponse { etag, body })?).await?;
+        Ok(())
+    }
+
+    /// Waits until the rate limit of `host` is reset if it was used up.
+    async fn wait_for_reset(&self, host: &str) -> anyhow::Result<()> {
+        let reset = self
+            .resets
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(host)
+            .copied();
+        let Some(wait) = reset.and_then(|reset| reset.duration_since(SystemTime::now()).ok())
+        else {
+            return Ok(());
+        };
+        if wait > self.max_wait {
+            anyhow::bail!(
+                "the API rate limit of {} is used up for another {}s, try again later or raise api_max_wait",
+                host,
+                wait.as_secs() + 1
+            );
+        }
+        debug!("rate limit of {} used up, waiting {:?}", host, wait);
+        tokio::time::sleep(wait).await;
+        Ok(())
+    }
+}
+
+/// When the rate limit is reset if a response says it is used up, from the
+/// headers of GitHub and Gitea, `x-ratelimit-*`, of GitLab, `ratelimit-*`,
+/// or `retry-after`.
+fn rate_limit_reset(status: StatusCode, headers: &HeaderMap) -> Option<SystemTime> {
+    let header = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            headers
+                .get(*name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        })
+    };
+    let limited = status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS;
+    if let Some(seconds) = header(&[RETRY_AFTER.as_str()]).filter(|_| limited) {
+        return Some(SystemTime::now() + Duration::from_secs(seconds));
+    }
+    if header(&["x-ratelimit-remaining", "ratelimit-remaining"]) != Some(0) {
+        return None;
+    }
+    let reset = header(&["x-ratelimit-reset", "ratelimit-reset"])?;
+    Some(UNIX_EPOCH + Duration::from_secs(reset))
+}
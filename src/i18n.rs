@@ -92,6 +92,8 @@ impl I18N {
 
 fn fluent_bundle_en_us() -> FluentBundle<FluentResource> {
     let mut bundle = FluentBundle::new(vec![langid!("en-US")]);
+    // unicode isolation marks show up as garbage in most terminals
+    bundle.set_use_isolating(false);
     bundle
         .add_resource(
             FluentResource::try_new(include_str!("../assets/lang/en_US.ftl").to_string())
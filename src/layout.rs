@@ -0,0 +1,86 @@
+//! Where repositories live: `<root>/<hostname>/<owner>/<name>`, the root of a
+//! type being `<repo_dir>/<type>` unless it is configured in `[roots]`.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+pub struct Layout {
+    repo_dir: PathBuf,
+    roots: BTreeMap<String, PathBuf>,
+}
+
+impl Layout {
+    pub fn new(repo_dir: PathBuf, roots: Option<&BTreeMap<String, String>>) -> Self {
+        Self {
+            repo_dir,
+            roots: roots
+                .into_iter()
+                .flatten()
+                .map(|(ty, root)| (ty.to_owned(), expand_home(root)))
+                .collect(),
+        }
+    }
+
+    pub fn root_of(&self, ty: &str) -> PathBuf {
+        self.roots
+            .get(ty)
+            .cloned()
+            .unwrap_or_else(|| self.repo_dir.join(ty))
+    }
+
+    pub fn path_of(&self, ty: &str, hostname: &str, owner: &str, name: &str) -> PathBuf {
+        self.root_of(ty).join(hostname).join(owner).join(name)
+    }
+
+    /// The types with their roots, the configured ones and those found in the
+    /// repository directory. Missing roots are left out.
+    pub fn roots(&self) -> anyhow::Result<Vec<(String, PathBuf)>> {
+        let mut roots = self.roots.clone();
+        if self.repo_dir.is_dir() {
+            for entry in std::fs::read_dir(&self.repo_dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    let ty = entry.file_name().to_string_lossy().to_string();
+                    roots.entry(ty).or_insert_with(|| entry.path());
+                }
+            }
+        }
+        Ok(roots
+            .into_iter()
+            .filter(|(_, root)| root.is_dir())
+            .collect())
+    }
+}
+
+/// The owner of the repository at `path` below the directory of its host, the
+/// directory at the owner level of the layout, empty for repositories right
+/// below the host. Repositories of subgroups like `group/sub/name` belong to
+/// the top level group.
+pub fn owner_of(path: &Path) -> String {
+    let mut components = path.components();
+    match (components.next(), components.next()) {
+        (Some(owner), Some(_)) => owner.as_os_str().to_string_lossy().to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Splits the path of a repository below its host into its namespace on the
+/// forge, everything before the last segment, and the name. The namespace is
+/// empty for top level repositories.
+pub fn split_path(path: &str) -> (&str, &str) {
+    path.rsplit_once('/').unwrap_or(("", path))
+}
+
+/// Expands a leading `~` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = || directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match home() {
+            Some(home) => home.join(rest.trim_start_matches('/')),
+            None => PathBuf::from(path),
+        },
+        _ => PathBuf::from(path),
+    }
+}
@@ -1,12 +1,60 @@
+mod audit;
 mod cli;
+mod clipboard;
 mod config;
+mod deps;
+mod devcontainer;
+mod forge;
+mod fs;
 mod git;
+mod http;
 mod i18n;
+mod layout;
+mod manifest;
+mod meta;
+mod outcome;
+mod plugin;
+mod porcelain;
+mod query;
+mod queue;
+mod release;
 mod rer;
+mod scaffold;
+mod script;
+mod secrets;
+mod style;
+mod template;
+mod toolchain;
+mod vendor;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     env_logger::init();
-    rer::Rer::parse().await?.run().await?;
+    let rer = rer::Rer::parse().await?;
+    let result = if rer.cancellable() {
+        // the run is dropped on Ctrl-C, which kills its git processes and
+        // removes half created repositories before exiting
+        tokio::select! {
+            result = rer.run() => Some(result),
+            _ = tokio::signal::ctrl_c() => None,
+        }
+    } else {
+        // Ctrl-C is for the program the command may be waiting on, like the
+        // shell of enter, so rerman ignores it instead of leaving it orphaned
+        tokio::spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+        Some(rer.run().await)
+    };
+    match result {
+        Some(Err(err)) => {
+            rer.report_error(&err);
+            std::process::exit(1);
+        }
+        None => {
+            rer.report_cancelled();
+            std::process::exit(130);
+        }
+        Some(Ok(())) => {}
+    }
+    rer.notify_update().await;
     Ok(())
 }
@@ -0,0 +1,238 @@
+//! Repository inventories, in rerman's own format and the formats of myrepos,
+//! git-workspace and Google's repo tool.
+
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+
+use crate::git::GitUrl;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ManifestFormat {
+    Rerman,
+    Mr,
+    GitWorkspace,
+    RepoXml,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct Manifest {
+    #[serde(default, rename = "repo")]
+    pub repos: Vec<ManifestRepo>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct ManifestRepo {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub hostname: String,
+    pub path: String,
+    pub url: String,
+    /// Commit the repository is expected at, recorded by `export --commits`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl ManifestRepo {
+    /// Path of the repository relative to the repository directory.
+    fn dir(&self) -> String {
+        format!("{}/{}/{}", self.ty, self.hostname, self.path)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct GitWorkspaceLock<'a> {
+    repo: Vec<GitWorkspaceRepo<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct GitWorkspaceRepo<'a> {
+    path: String,
+    url: &'a str,
+}
+
+#[derive(serde::Deserialize)]
+struct GitWorkspaceImport {
+    #[serde(default)]
+    repo: Vec<GitWorkspaceImportRepo>,
+}
+
+#[derive(serde::Deserialize)]
+struct GitWorkspaceImportRepo {
+    url: String,
+}
+
+impl Manifest {
+    /// Renders the git repositories only for the formats of other tools.
+    pub fn export(&self, format: ManifestFormat) -> anyhow::Result<String> {
+        let git_repos = self.repos.iter().filter(|repo| repo.ty == "git");
+        Ok(match format {
+            ManifestFormat::Rerman => toml::to_string(self)?,
+            ManifestFormat::Mr => git_repos
+                .map(|repo| {
+                    let name = repo.path.rsplit('/').next().unwrap_or(&repo.path);
+                    format!(
+                        "[{}]\ncheckout = git clone {} {}\n\n",
+                        repo.dir(),
+                        shell_words::quote(&repo.url),
+                        shell_words::quote(name)
+                    )
+                })
+                .collect(),
+            ManifestFormat::GitWorkspace => toml::to_string(&GitWorkspaceLock {
+                repo: git_repos
+                    .map(|repo| GitWorkspaceRepo {
+                        path: repo.dir(),
+                        url: &repo.url,
+                    })
+                    .collect(),
+            })?,
+            ManifestFormat::RepoXml => repo_xml(git_repos),
+        })
+    }
+}
+
+impl Manifest {
+    /// Parses a manifest of `format`, guessed from the content if not given.
+    /// Repositories of other tools are placed by their url, like `clone` does.
+    pub fn import(text: &str, format: Option<ManifestFormat>) -> anyhow::Result<Self> {
+        let format = format.unwrap_or_else(|| {
+            if text.trim_start().starts_with('<') {
+                ManifestFormat::RepoXml
+            } else if text.contains("type = ") {
+                ManifestFormat::Rerman
+            } else {
+                ManifestFormat::GitWorkspace
+            }
+        });
+        let urls = match format {
+            ManifestFormat::Rerman => return Ok(toml::from_str(text)?),
+            ManifestFormat::Mr => anyhow::bail!("mr manifests can not be imported"),
+            ManifestFormat::GitWorkspace => toml::from_str::<GitWorkspaceImport>(text)?
+                .repo
+                .into_iter()
+                .map(|repo| repo.url)
+                .collect(),
+            ManifestFormat::RepoXml => repo_xml_urls(text)?,
+        };
+        Ok(Self {
+            repos: urls
+                .into_iter()
+                .map(|url| {
+                    let git_url = GitUrl::parse(&url)?;
+                    Ok(ManifestRepo {
+                        ty: "git".to_string(),
+                        hostname: git_url.host().to_string(),
+                        path: git_url.project_path(),
+                        url,
+                        commit: None,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+/// The clone urls of the projects of a repo tool manifest.
+fn repo_xml_urls(text: &str) -> anyhow::Result<Vec<String>> {
+    let document = roxmltree::Document::parse(text)?;
+    let manifest = document.root_element();
+    let remotes = manifest
+        .children()
+        .filter(|node| node.has_tag_name("remote"))
+        .filter_map(|node| Some((node.attribute("name")?, node.attribute("fetch")?)))
+        .collect::<BTreeMap<_, _>>();
+    let default_remote = manifest
+        .children()
+        .find(|node| node.has_tag_name("default"))
+        .and_then(|node| node.attribute("remote"));
+    manifest
+        .children()
+        .filter(|node| node.has_tag_name("project"))
+        .map(|project| {
+            let name = project
+                .attribute("name")
+                .ok_or_else(|| anyhow!("project without a name"))?;
+            let remote = project
+                .attribute("remote")
+                .or(default_remote)
+                .ok_or_else(|| anyhow!("project {} has no remote", name))?;
+            let fetch = remotes
+                .get(remote)
+                .ok_or_else(|| anyhow!("remote {} of project {} is not defined", remote, name))?;
+            if !fetch.contains(':') {
+                anyhow::bail!(
+                    "relative fetch url {} of remote {} is not supported",
+                    fetch,
+                    remote
+                );
+            }
+            Ok(format!(
+                "{}/{}",
+                fetch.strip_suffix('/').unwrap_or(fetch),
+                name
+            ))
+        })
+        .collect()
+}
+
+/// Splits `url` into the fetch base of a repo tool remote and the project name,
+/// scp-like urls are turned into `ssh://` ones as repo joins them with a `/`.
+fn split_fetch_url(url: &str) -> (String, &str) {
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let Some(scheme_end) = url.find("://").map(|index| index + 3) else {
+        return match url.split_once(':') {
+            Some((fetch, name)) => (format!("ssh://{}", fetch), name.trim_start_matches('/')),
+            None => (String::new(), url),
+        };
+    };
+    match url[scheme_end..].find('/') {
+        // urls without a host, like file:///path, keep the root in the fetch base
+        Some(0) => (url[..scheme_end + 1].to_string(), &url[scheme_end + 1..]),
+        Some(index) => (
+            url[..scheme_end + index].to_string(),
+            &url[scheme_end + index + 1..],
+        ),
+        None => (url.to_string(), ""),
+    }
+}
+
+fn repo_xml<'a>(repos: impl Iterator<Item = &'a ManifestRepo>) -> String {
+    let mut remotes = BTreeMap::<String, String>::new();
+    let mut projects = String::new();
+    for repo in repos {
+        let (fetch, name) = split_fetch_url(&repo.url);
+        if !remotes.contains_key(&fetch) {
+            let name = if remotes.values().any(|name| name == &repo.hostname) {
+                format!("{}-{}", repo.hostname, remotes.len())
+            } else {
+                repo.hostname.to_owned()
+            };
+            remotes.insert(fetch.to_owned(), name);
+        }
+        let remote = &remotes[&fetch];
+        projects += &format!(
+            "  <project name=\"{}\" path=\"{}\" remote=\"{}\" />\n",
+            xml_escape(name),
+            xml_escape(&repo.dir()),
+            xml_escape(remote)
+        );
+    }
+    let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<manifest>\n".to_string();
+    for (fetch, name) in &remotes {
+        xml += &format!(
+            "  <remote name=\"{}\" fetch=\"{}\" />\n",
+            xml_escape(name),
+            xml_escape(fetch)
+        );
+    }
+    xml += &projects;
+    xml + "</manifest>\n"
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
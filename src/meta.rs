@@ -0,0 +1,102 @@
+//! Per repository metadata, kept in `.git/rerman.toml`, or in `.rerman.toml`
+//! for repositories without a `.git` directory.
+
+use std::path::{Path, PathBuf};
+
+/// Marks the root of repositories not managed by a VCS, like vendored archives.
+pub const META_FILE: &str = ".rerman.toml";
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct RepoMeta {
+    pub vendor: Option<VendorMeta>,
+    /// Description of the repository on its forge.
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+    /// Opener used when `open` is called without `--with`.
+    pub opener: Option<String>,
+    /// Command run by `enter` to get a dev environment.
+    pub enter: Option<String>,
+    /// User that cloned or created the repository, telling them apart in
+    /// shared roots.
+    pub cloned_by: Option<String>,
+    /// Where a repository of a plugin type was cloned from.
+    pub url: Option<String>,
+    /// Directories in the repository registered as repositories of their own
+    /// by `subrepo add`, like `services/payments`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subrepos: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct VendorMeta {
+    pub url: String,
+    pub sha256: String,
+}
+
+pub fn meta_file(dir: impl AsRef<Path>) -> PathBuf {
+    let git_dir = dir.as_ref().join(".git");
+    if git_dir.is_dir() {
+        git_dir.join("rerman.toml")
+    } else {
+        dir.as_ref().join(META_FILE)
+    }
+}
+
+impl RepoMeta {
+    pub async fn load(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        match tokio::fs::read_to_string(meta_file(dir)).await {
+            Ok(meta) => Ok(toml::from_str(&meta)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn save(&self, dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        tokio::fs::write(meta_file(dir), toml::to_string(self)?).await?;
+        Ok(())
+    }
+}
+
+/// The user running rerman, the one who invoked sudo when run through it.
+pub fn current_user() -> Option<String> {
+    ["SUDO_USER", "USER", "LOGNAME", "USERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|user| !user.is_empty()))
+}
+
+/// Finds the directories below `base` containing `marker`, without descending
+/// into them, adding the directories looked into to `visited`.
+pub async fn filter_repo_paths_recursively(
+    base: impl AsRef<Path>,
+    marker: &str,
+    visited: &mut Vec<PathBuf>,
+) -> anyhow::Result<Vec<PathBuf>> {
+    visited.push(base.as_ref().to_path_buf());
+    let mut entries = tokio::fs::read_dir(base.as_ref()).await?;
+    let mut paths = vec![];
+    loop {
+        match entries.next_entry().await {
+            Err(err) => {
+                return Err(anyhow::Error::from(err));
+            }
+            Ok(None) => break,
+            Ok(Some(entry)) => {
+                if entry.file_type().await?.is_dir() {
+                    let entry_path = entry.path();
+                    if tokio::fs::try_exists(entry_path.join(marker)).await? {
+                        paths.push(entry_path);
+                    } else {
+                        paths.append(
+                            &mut Box::pin(filter_repo_paths_recursively(
+                                entry_path, marker, visited,
+                            ))
+                            .await?,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(paths)
+}
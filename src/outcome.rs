@@ -0,0 +1,64 @@
+//! Machine readable results of mutating commands, printed with `--json`.
+
+use std::time::Instant;
+
+/// Lines of stderr kept in an outcome.
+const STDERR_TAIL_LINES: usize = 10;
+
+#[derive(serde::Serialize)]
+pub struct Outcome {
+    pub path: String,
+    pub duration_ms: u128,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub stderr_tail: String,
+    /// Whether anything changed, for `update`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<bool>,
+    /// Whether a repository without upstream was left alone, for `update`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<bool>,
+    /// The new url of a repository that moved upstream, for `update`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moved_to: Option<String>,
+    /// The url a repository was published to, for `create --publish`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_to: Option<String>,
+}
+
+impl Outcome {
+    pub fn new(path: impl AsRef<std::path::Path>, start: Instant) -> Self {
+        Self {
+            path: path.as_ref().to_string_lossy().to_string(),
+            duration_ms: start.elapsed().as_millis(),
+            success: true,
+            exit_code: None,
+            stderr_tail: String::new(),
+            updated: None,
+            skipped: None,
+            moved_to: None,
+            published_to: None,
+        }
+    }
+
+    /// Records the exit status and the tail of the stderr of a process.
+    pub fn with_exit(mut self, status: std::process::ExitStatus, stderr: &str) -> Self {
+        self.success = status.success();
+        self.exit_code = status.code();
+        self.stderr_tail = tail(stderr);
+        self
+    }
+
+    /// Records a failure that did not come from a process exiting.
+    pub fn with_error(mut self, err: &anyhow::Error) -> Self {
+        self.success = false;
+        self.stderr_tail = tail(&err.to_string());
+        self
+    }
+}
+
+fn tail(text: &str) -> String {
+    let lines = text.trim_end().lines().collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+}
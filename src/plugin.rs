@@ -0,0 +1,254 @@
+//! WASM plugins adding repository types and forges, the `.wasm` and `.wat`
+//! files in the `plugins` directory next to the config.
+//!
+//! Plugins exchange JSON messages through their memory. A plugin exports
+//! `memory`, `rerman_alloc(len: i32) -> i32` returning a buffer the host
+//! writes a message of `len` bytes to, `rerman_manifest() -> i64` returning
+//! what it provides, like `{"name": "p4", "types": ["p4"], "forges": []}`,
+//! and `rerman_call(ptr: i32, len: i32) -> i64` answering a request. Messages
+//! returned are packed as `ptr << 32 | len`, and answers with an `error`
+//! field are failures.
+//!
+//! Requests have an `op`:
+//!
+//! - `locate` with `type` and `url`: where the repository goes, as
+//!   `{"hostname": ..., "path": ...}`
+//! - `clone` with `type`, `url` and `dir`
+//! - `update` with `type`, `url` and `dir`, answered with `{"updated": bool}`
+//! - `repo_info` with `forge`, `host` and `project`, answered with
+//!   `{"description": ..., "topics": [...]}`
+//!
+//! Plugins can import from the `rerman` module `run(ptr, len) -> i64`, taking
+//! `{"args": [...], "dir": ...}` and returning `{"status", "stdout",
+//! "stderr"}`, and `http_get(ptr, len) -> i64`, taking `{"url": ...}` and
+//! returning `{"status", "body"}`.
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use log::warn;
+use serde::de::DeserializeOwned;
+use wasmi::{AsContextMut, Caller, Engine, Extern, Instance, Linker, Memory, Module, Store};
+
+#[derive(Clone)]
+pub struct Plugin {
+    pub name: String,
+    pub types: Vec<String>,
+    pub forges: Vec<String>,
+    engine: Engine,
+    module: Module,
+}
+
+#[derive(serde::Deserialize)]
+struct PluginManifest {
+    name: String,
+    #[serde(default)]
+    types: Vec<String>,
+    #[serde(default)]
+    forges: Vec<String>,
+}
+
+/// The plugins in `dir`, leaving out those failing to load.
+pub fn discover(dir: &Path) -> anyhow::Result<Vec<Plugin>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut plugins = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("wasm" | "wat")
+        ) {
+            continue;
+        }
+        match Plugin::load(&path) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(err) => warn!("loading plugin {:?} failed: {:?}", path, err),
+        }
+    }
+    Ok(plugins)
+}
+
+impl Plugin {
+    pub fn load(file: &Path) -> anyhow::Result<Self> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, std::fs::read(file)?)?;
+        let mut plugin = Self {
+            name: String::new(),
+            types: vec![],
+            forges: vec![],
+            engine,
+            module,
+        };
+        let (mut store, instance) = plugin.instantiate()?;
+        let manifest = instance
+            .get_typed_func::<(), i64>(&store, "rerman_manifest")?
+            .call(&mut store, ())?;
+        let memory = memory_of(&instance, &store)?;
+        let manifest: PluginManifest = serde_json::from_slice(&read(&memory, &store, manifest)?)?;
+        plugin.name = manifest.name;
+        plugin.types = manifest.types;
+        plugin.forges = manifest.forges;
+        Ok(plugin)
+    }
+
+    /// Sends `request` to the plugin. This blocks for as long as the plugin
+    /// runs, see [`Plugin::call_async`].
+    pub fn call<R: DeserializeOwned>(&self, request: &serde_json::Value) -> anyhow::Result<R> {
+        let (mut store, instance) = self.instantiate()?;
+        let memory = memory_of(&instance, &store)?;
+        let request = write(
+            &memory,
+            &instance,
+            &mut store,
+            &serde_json::to_vec(request)?,
+        )?;
+        let response = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "rerman_call")?
+            .call(&mut store, ((request >> 32) as i32, request as i32))?;
+        let response: serde_json::Value =
+            serde_json::from_slice(&read(&memory, &store, response)?)?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!(
+                "plugin {}: {}",
+                self.name,
+                error
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or(error.to_string())
+            ));
+        }
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Sends `request` to the plugin on a thread where it may block.
+    pub async fn call_async<R: DeserializeOwned + Send + 'static>(
+        &self,
+        request: serde_json::Value,
+    ) -> anyhow::Result<R> {
+        let plugin = self.clone();
+        tokio::task::spawn_blocking(move || plugin.call(&request)).await?
+    }
+
+    fn instantiate(&self) -> anyhow::Result<(Store<()>, Instance)> {
+        let mut store = Store::new(&self.engine, ());
+        let mut linker = Linker::<()>::new(&self.engine);
+        linker.func_wrap(
+            "rerman",
+            "run",
+            |caller: Caller<'_, ()>, ptr: i32, len: i32| host_call(caller, ptr, len, host_run),
+        )?;
+        linker.func_wrap(
+            "rerman",
+            "http_get",
+            |caller: Caller<'_, ()>, ptr: i32, len: i32| host_call(caller, ptr, len, host_http_get),
+        )?;
+        let instance = linker.instantiate_and_start(&mut store, &self.module)?;
+        Ok((store, instance))
+    }
+}
+
+fn memory_of(instance: &Instance, store: &Store<()>) -> anyhow::Result<Memory> {
+    instance
+        .get_memory(store, "memory")
+        .ok_or_else(|| anyhow!("plugin exports no memory"))
+}
+
+/// Reads the message at the packed `ptr << 32 | len`.
+fn read(memory: &Memory, store: &Store<()>, packed: i64) -> anyhow::Result<Vec<u8>> {
+    let (ptr, len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+    memory
+        .data(store)
+        .get(ptr..ptr + len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| anyhow!("plugin message out of bounds"))
+}
+
+/// Writes `message` into a buffer of the plugin, returning it packed.
+fn write(
+    memory: &Memory,
+    instance: &Instance,
+    mut store: impl AsContextMut,
+    message: &[u8],
+) -> anyhow::Result<i64> {
+    let alloc = instance.get_typed_func::<i32, i32>(&store, "rerman_alloc")?;
+    let ptr = alloc.call(&mut store, message.len() as i32)?;
+    memory.write(&mut store, ptr as u32 as usize, message)?;
+    Ok((ptr as u32 as i64) << 32 | message.len() as i64)
+}
+
+/// Answers a call of the plugin to the host with `f`.
+fn host_call(
+    mut caller: Caller<'_, ()>,
+    ptr: i32,
+    len: i32,
+    f: fn(serde_json::Value) -> anyhow::Result<serde_json::Value>,
+) -> Result<i64, wasmi::Error> {
+    let error = |err: anyhow::Error| wasmi::Error::new(err.to_string());
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("plugin exports no memory"))?;
+    let alloc = caller
+        .get_export("rerman_alloc")
+        .and_then(Extern::into_func)
+        .ok_or_else(|| wasmi::Error::new("plugin exports no rerman_alloc"))?
+        .typed::<i32, i32>(&caller)?;
+    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+    let request = memory
+        .data(&caller)
+        .get(ptr..ptr + len)
+        .ok_or_else(|| wasmi::Error::new("plugin message out of bounds"))?;
+    let request = serde_json::from_slice(request).map_err(|err| error(err.into()))?;
+    let response = match f(request) {
+        Ok(response) => response,
+        Err(err) => serde_json::json!({ "error": err.to_string() }),
+    };
+    let response = serde_json::to_vec(&response).map_err(|err| error(err.into()))?;
+    let ptr = alloc.call(&mut caller, response.len() as i32)?;
+    memory
+        .write(&mut caller, ptr as u32 as usize, &response)
+        .map_err(|err| error(err.into()))?;
+    Ok((ptr as u32 as i64) << 32 | response.len() as i64)
+}
+
+#[derive(serde::Deserialize)]
+struct RunRequest {
+    args: Vec<String>,
+    dir: Option<String>,
+}
+
+fn host_run(request: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+    let request: RunRequest = serde_json::from_value(request)?;
+    let (program, args) = request
+        .args
+        .split_first()
+        .ok_or_else(|| anyhow!("empty command"))?;
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+    if let Some(dir) = request.dir {
+        command.current_dir(dir);
+    }
+    let output = command.output()?;
+    Ok(serde_json::json!({
+        "status": output.status.code(),
+        "stdout": String::from_utf8_lossy(&output.stdout),
+        "stderr": String::from_utf8_lossy(&output.stderr),
+    }))
+}
+
+#[derive(serde::Deserialize)]
+struct HttpGetRequest {
+    url: String,
+}
+
+fn host_http_get(request: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+    let request: HttpGetRequest = serde_json::from_value(request)?;
+    // plugins run on blocking threads of the runtime, see `Plugin::call_async`
+    tokio::runtime::Handle::current().block_on(async {
+        let response = crate::http::client()?.get(request.url).send().await?;
+        let status = response.status().as_u16();
+        Ok(serde_json::json!({ "status": status, "body": response.text().await? }))
+    })
+}
@@ -0,0 +1,20 @@
+//! Stable, tab separated output for scripts, see the README for the formats.
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum PorcelainVersion {
+    V1,
+}
+
+/// Joins the fields of one record, escaping the characters used as separators.
+pub fn record<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    fields
+        .into_iter()
+        .map(|field| {
+            field
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
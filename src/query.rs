@@ -0,0 +1,80 @@
+//! Filter expressions of `--where`, rhai expressions over the fields of a
+//! repository as variables, like `host == "github.com" && dirty && size > 100MB`.
+//! Sizes may be written with a unit, `KB`, `MB`, `GB` and `TB` being powers
+//! of 1000 and `KiB`, `MiB`, `GiB` and `TiB` powers of 1024.
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use lazy_regex::{regex, regex_replace_all};
+use rhai::{Dynamic, Engine, Scope, AST};
+
+pub struct Query {
+    engine: Engine,
+    ast: AST,
+    idents: Vec<String>,
+}
+
+impl Query {
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let source = regex_replace_all!(
+            r#""(?:[^"\\]|\\.)*"|\b(\d+(?:\.\d+)?)\s*([KMGT]i?B)\b"#,
+            source,
+            |whole: &str, number: &str, unit: &str| {
+                if unit.is_empty() {
+                    return whole.to_string();
+                }
+                let base: f64 = if unit.contains('i') { 1024.0 } else { 1000.0 };
+                let power = "KMGT".find(&unit[..1]).unwrap_or_default() as i32 + 1;
+                let bytes = number.parse::<f64>().unwrap_or_default() * base.powi(power);
+                (bytes as i64).to_string()
+            }
+        );
+        let engine = Engine::new();
+        let ast = engine
+            .compile_expression(source.as_ref())
+            .map_err(|err| anyhow!("--where {}: {}", source, err))?;
+        let idents = regex!(r#""(?:[^"\\]|\\.)*"|([A-Za-z_][A-Za-z0-9_]*)"#)
+            .captures_iter(&source)
+            .filter_map(|captures| captures.get(1))
+            .map(|ident| ident.as_str().to_string())
+            .collect();
+        Ok(Self {
+            engine,
+            ast,
+            idents,
+        })
+    }
+
+    /// Whether the expression mentions the variable `field`.
+    pub fn uses(&self, field: &str) -> bool {
+        self.idents.iter().any(|ident| ident == field)
+    }
+
+    /// Whether the repository with the fields `repo`, and the name, host and
+    /// dir derived from them, matches. Fields missing from `repo` are `()`.
+    pub fn matches(&self, repo: rhai::Map, dir: &Path) -> anyhow::Result<bool> {
+        let mut scope = Scope::new();
+        for ident in &self.idents {
+            scope.push_dynamic(ident.as_str(), Dynamic::UNIT);
+        }
+        if let Some(hostname) = repo.get("hostname") {
+            scope.push_dynamic("host", hostname.clone());
+        }
+        if let Some(path) = repo.get("path") {
+            let path = path.to_string();
+            scope.push("name", path.rsplit('/').next().unwrap_or(&path).to_string());
+        }
+        scope.push("dir", dir.to_string_lossy().to_string());
+        for (field, value) in repo {
+            scope.push_dynamic(field.as_str(), value);
+        }
+        let value = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|err| anyhow!("--where: {}", err))?;
+        value
+            .as_bool()
+            .map_err(|ty| anyhow!("--where returned {} instead of a bool", ty))
+    }
+}
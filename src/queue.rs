@@ -0,0 +1,67 @@
+//! Queue of a bulk clone, persisted after every repository so an interrupted
+//! run can be resumed.
+
+use std::path::Path;
+
+use crate::manifest::ManifestRepo;
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct CloneQueue {
+    #[serde(default, rename = "item")]
+    pub items: Vec<QueueItem>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct QueueItem {
+    #[serde(flatten)]
+    pub repo: ManifestRepo,
+    pub state: QueueState,
+    pub error: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueState {
+    Pending,
+    Done,
+    Failed,
+}
+
+impl CloneQueue {
+    pub fn new(repos: Vec<ManifestRepo>) -> Self {
+        Self {
+            items: repos
+                .into_iter()
+                .map(|repo| QueueItem {
+                    repo,
+                    state: QueueState::Pending,
+                    error: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Loads the queue in `file`, `None` if there is none.
+    pub async fn load(file: &Path) -> anyhow::Result<Option<Self>> {
+        match tokio::fs::read_to_string(file).await {
+            Ok(queue) => Ok(Some(toml::from_str(&queue)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves the queue to `file`, or removes it once everything is done.
+    pub async fn save(&self, file: &Path) -> anyhow::Result<()> {
+        if self.items.iter().all(|item| item.state == QueueState::Done) {
+            return match tokio::fs::remove_file(file).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+                _ => Ok(()),
+            };
+        }
+        if let Some(parent) = file.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(file, toml::to_string(self)?).await?;
+        Ok(())
+    }
+}
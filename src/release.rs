@@ -0,0 +1,139 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+
+use crate::http::{self, sha256_hex};
+
+pub const DEFAULT_RELEASE_URL: &str =
+    "https://api.github.com/repos/zerodegress/rerman/releases/latest";
+
+/// A release in the format of the GitHub Releases API.
+#[derive(serde::Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    pub async fn fetch(url: impl AsRef<str>) -> anyhow::Result<Self> {
+        Ok(http::client()?
+            .get(url.as_ref())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub fn version(&self) -> anyhow::Result<semver::Version> {
+        Ok(semver::Version::parse(
+            self.tag_name.strip_prefix('v').unwrap_or(&self.tag_name),
+        )?)
+    }
+
+    pub fn is_newer(&self) -> anyhow::Result<bool> {
+        Ok(self.version()? > current_version())
+    }
+
+    /// The asset built for the running platform, e.g. `rerman-x86_64-linux`.
+    pub fn binary_asset(&self) -> Option<&ReleaseAsset> {
+        let name = format!(
+            "rerman-{}-{}{}",
+            std::env::consts::ARCH,
+            std::env::consts::OS,
+            std::env::consts::EXE_SUFFIX
+        );
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    pub fn checksum_asset(&self, binary: &ReleaseAsset) -> Option<&ReleaseAsset> {
+        let name = format!("{}.sha256", binary.name);
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Returns the latest released version, only asking the release url if the
+/// answer cached in `cache_file` is older than a day.
+pub async fn latest_version_cached(
+    cache_file: PathBuf,
+    url: impl AsRef<str>,
+) -> anyhow::Result<semver::Version> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    // a cache that does not parse is refetched like a missing one
+    if let Ok(cache) = tokio::fs::read_to_string(&cache_file).await {
+        if let Some((Ok(checked_at), Ok(version))) =
+            cache.trim().split_once('\n').map(|(checked_at, version)| {
+                (checked_at.parse::<u64>(), semver::Version::parse(version))
+            })
+        {
+            if now.saturating_sub(checked_at) < 24 * 60 * 60 {
+                return Ok(version);
+            }
+        }
+    }
+    let fetched = match tokio::time::timeout(Duration::from_secs(3), Release::fetch(url)).await {
+        Ok(release) => release.and_then(|release| release.version()),
+        Err(elapsed) => Err(elapsed.into()),
+    };
+    // failed checks are cached too, so being offline does not slow every command
+    let version = fetched
+        .as_ref()
+        .cloned()
+        .unwrap_or_else(|_| current_version());
+    if let Some(dir) = cache_file.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(&cache_file, format!("{}\n{}\n", now, version)).await?;
+    fetched
+}
+
+pub fn current_version() -> semver::Version {
+    semver::Version::parse(env!("CARGO_PKG_VERSION")).expect("invalid package version")
+}
+
+/// Checks `data` against a checksum file in the `sha256sum` format.
+pub fn verify_sha256(data: &[u8], checksum_file: &str) -> anyhow::Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("empty checksum file"))?
+        .to_lowercase();
+    let actual = sha256_hex(data);
+    if expected != actual {
+        return Err(anyhow!(
+            "checksum mismatch: expected {}, got {}",
+            expected,
+            actual
+        ));
+    }
+    Ok(())
+}
+
+/// Replaces the binary at `exe` with `data`, going through a temporary file
+/// next to it so that a failed write never leaves a broken binary behind.
+pub async fn replace_binary(exe: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let new_exe = exe.with_extension("new");
+    tokio::fs::write(&new_exe, data).await?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&new_exe, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+    if cfg!(windows) {
+        // a running executable can not be overwritten on windows, but renamed
+        let old_exe = exe.with_extension("old");
+        let _ = tokio::fs::remove_file(&old_exe).await;
+        tokio::fs::rename(exe, &old_exe).await?;
+    }
+    tokio::fs::rename(&new_exe, exe).await?;
+    Ok(())
+}
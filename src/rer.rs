@@ -1,17 +1,71 @@
-use std::{env::current_dir, path::PathBuf, process::Stdio};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env::current_dir,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::OnceLock,
+    time::Instant,
+};
 
 use anyhow::anyhow;
-use clap::Parser;
+use clap::ValueEnum;
+use log::{debug, warn};
 use tabled::Tabled;
 use unic_langid::{langid, LanguageIdentifier};
 
 use crate::{
-    cli::{Cli, Commands, DebugCommands},
-    config::Config,
-    git::{filter_git_paths_recursively, Git, GitUrl},
+    cli::{Cli, Commands, ConfigCommands, DebugCommands, LayoutCommands, RepoColumn, RepoFilter},
+    config::{self, Config, HostConfig, TableStyle},
+    devcontainer,
+    forge::Forge,
+    fs,
+    git::{self, Git, GitUrl, Protocol},
+    http,
     i18n::I18N,
+    layout::{self, Layout},
+    meta::{self, RepoMeta},
+    outcome::Outcome,
+    porcelain::{self, PorcelainVersion},
+    query::Query,
+    release::{self, current_version, Release, DEFAULT_RELEASE_URL},
+    script::Script,
+    style::{Paint, Painter},
+    template, toolchain,
 };
 
+mod apply;
+mod audit;
+mod branches;
+mod browse;
+mod cache;
+mod clone;
+mod commit_all;
+mod copy;
+mod create;
+mod discover;
+mod enter;
+mod exec;
+mod group;
+mod hooks;
+mod index;
+mod init;
+mod jobs;
+mod layout_migrate;
+mod manifest;
+mod mirror;
+mod open;
+mod plugins;
+mod push;
+mod refresh_meta;
+mod remotes;
+mod subrepo;
+mod sync_fork;
+mod target;
+mod templates;
+mod timeline;
+mod update;
+mod watch;
+
 #[derive(Debug, Clone)]
 pub enum RerSetup {
     System,
@@ -26,27 +80,81 @@ pub struct Rer {
     config: Config,
     i18n: I18N,
     lang_id: LanguageIdentifier,
+    painter: Painter,
+    /// Shared by all requests to forges, made when first needed.
+    api: OnceLock<http::ApiClient>,
 }
 
-#[derive(Tabled, serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct RepoTableItem {
     path: String,
-    #[tabled(rename = "type")]
     #[serde(rename = "type")]
     ty: String,
     hostname: String,
+    owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    topics: Vec<String>,
+    devcontainer: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subrepos: Vec<String>,
 }
 
-impl Rer {
-    fn default_open_with(&self) -> Option<String> {
-        self.config.open_with.to_owned()
+impl RepoTableItem {
+    fn column(&self, column: RepoColumn) -> String {
+        match column {
+            RepoColumn::Path => self.path.to_owned(),
+            RepoColumn::Type => self.ty.to_owned(),
+            RepoColumn::Hostname => self.hostname.to_owned(),
+            RepoColumn::Owner => self.owner.to_owned(),
+            RepoColumn::Branch => self.branch.to_owned().unwrap_or_default(),
+            RepoColumn::User => self.user.to_owned().unwrap_or_default(),
+            RepoColumn::Description => self.description.to_owned().unwrap_or_default(),
+            RepoColumn::Topics => self.topics.join(", "),
+            RepoColumn::Devcontainer => if self.devcontainer { "yes" } else { "" }.to_string(),
+            RepoColumn::Language => self.language.to_owned().unwrap_or_default(),
+        }
     }
 
+    /// The subrepo `sub` of the repository, addressed like a repository whose
+    /// path is `sub` below that of its parent.
+    fn subrepo(&self, sub: &str) -> RepoTableItem {
+        RepoTableItem {
+            path: format!("{}/{}", self.path, sub),
+            language: None,
+            subrepos: vec![],
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Tabled)]
+pub struct RepoStatusItem {
+    path: String,
+    #[tabled(rename = "type")]
+    ty: String,
+    hostname: String,
+    branch: String,
+    status: String,
+}
+
+impl Rer {
     fn repo_dir(&self) -> anyhow::Result<PathBuf> {
+        self.repo_dir_of(&self.setup)
+    }
+
+    fn repo_dir_of(&self, setup: &RerSetup) -> anyhow::Result<PathBuf> {
         if let Some(ref repo_dir) = self.config.repo_dir {
             Ok(PathBuf::from(repo_dir))
         } else {
-            match self.setup {
+            match setup {
                 RerSetup::Local => Ok(current_dir()?.join(".rerman").join("repositories")),
                 RerSetup::User => {
                     let base_dirs = directories::BaseDirs::new().ok_or_else(|| {
@@ -95,18 +203,143 @@ impl Rer {
         &self,
         ty: impl AsRef<str>,
         hostname: impl AsRef<str>,
-        username: impl AsRef<str>,
-        path: impl AsRef<str>,
+        owner: impl AsRef<str>,
+        name: impl AsRef<str>,
     ) -> anyhow::Result<PathBuf> {
-        Ok((self.repo_dir()?)
-            .join(ty.as_ref())
-            .join(hostname.as_ref())
-            .join(username.as_ref())
-            .join(path.as_ref()))
+        Ok(self.layout()?.path_of(
+            ty.as_ref(),
+            hostname.as_ref(),
+            owner.as_ref(),
+            name.as_ref(),
+        ))
+    }
+
+    fn layout(&self) -> anyhow::Result<Layout> {
+        Ok(Layout::new(self.repo_dir()?, self.config.roots.as_ref()))
+    }
+
+    /// Directory for state rerman keeps besides the repositories, like caches.
+    fn data_dir(&self) -> anyhow::Result<PathBuf> {
+        match self.setup {
+            RerSetup::Local => Ok(current_dir()?.join(".rerman")),
+            RerSetup::System if cfg!(target_os = "linux") => Ok(PathBuf::from("/var/lib/rerman")),
+            _ => {
+                let base_dirs = directories::BaseDirs::new().ok_or_else(|| {
+                    anyhow!(
+                        "{}",
+                        self.i18n
+                            .format_msg_or_log(&self.lang_id, "error-get-base-dirs", None)
+                    )
+                })?;
+                Ok(base_dirs.data_local_dir().join("rerman"))
+            }
+        }
+    }
+
+    /// Records the current user as the one who cloned the repository in `dir`.
+    async fn record_cloned_by(&self, dir: &Path) {
+        let result = async {
+            let mut meta = RepoMeta::load(dir).await?;
+            meta.cloned_by = meta::current_user();
+            meta.save(dir).await
+        };
+        if let Err(err) = result.await {
+            warn!("recording the user who cloned {:?} failed: {:?}", dir, err);
+        }
+    }
+
+    /// The log of layout changes rerman made on its own, see [`crate::audit`].
+    fn audit_file(&self) -> anyhow::Result<PathBuf> {
+        Ok(self.data_dir()?.join("audit.log"))
+    }
+
+    /// The client of the APIs of forges, caching in the data directory.
+    fn api(&self) -> anyhow::Result<&http::ApiClient> {
+        if let Some(api) = self.api.get() {
+            return Ok(api);
+        }
+        let api = http::ApiClient::new(
+            self.data_dir()?.join("api-cache"),
+            self.config.api_parallel.unwrap_or(4),
+            std::time::Duration::from_secs(self.config.api_max_wait.unwrap_or(60)),
+        )?;
+        Ok(self.api.get_or_init(|| api))
+    }
+
+    fn git(&self) -> Git {
+        Git::with_timeout(
+            self.cli
+                .timeout
+                .or(self.config.timeout)
+                .map(std::time::Duration::from_secs),
+        )
+    }
+
+    fn host_config(&self, host: &str) -> Option<&HostConfig> {
+        self.config.hosts.as_ref()?.get(host)
+    }
+
+    /// Protocol to clone shorthand targets of `host` with, https by default.
+    fn protocol_of_host(&self, host: &str) -> Protocol {
+        self.host_config(host)
+            .and_then(|host| host.protocol)
+            .or(self.config.protocol)
+            .unwrap_or(Protocol::Https)
+    }
+
+    /// The name of the forge configured for `host`.
+    fn forge_of_host(&self, host: &str) -> Option<&str> {
+        self.host_config(host)
+            .and_then(|host| host.forge.as_deref())
+    }
+
+    /// The forge of `hostname` with the access token for it.
+    fn forge_with_token(&self, hostname: &str) -> anyhow::Result<(Forge, String)> {
+        let forge = match self.forge_of_host(hostname) {
+            Some(name) => Forge::from_name(name),
+            None => Forge::detect(hostname),
+        }
+        .ok_or_else(|| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-no-forge-of-host",
+                    Some(vec![("host".to_string(), hostname.to_string())])
+                )
+            )
+        })?;
+        let token_env = self
+            .host_config(hostname)
+            .and_then(|host| host.token_env.as_deref())
+            .unwrap_or(forge.token_env());
+        let token = std::env::var(token_env).map_err(|_| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-no-forge-token",
+                    Some(vec![
+                        ("host".to_string(), hostname.to_string()),
+                        ("env".to_string(), token_env.to_string()),
+                    ])
+                )
+            )
+        })?;
+        Ok((forge, token))
+    }
+
+    fn path_of_git_url(&self, url: &GitUrl) -> anyhow::Result<PathBuf> {
+        let (host, owner, name) = url.canonical();
+        self.path_of_repo("git", &host, &owner, &name)
     }
 
     fn config_file(&self) -> anyhow::Result<PathBuf> {
-        Ok(match self.setup {
+        self.config_file_of(&self.setup)
+    }
+
+    fn config_file_of(&self, setup: &RerSetup) -> anyhow::Result<PathBuf> {
+        Ok(match setup {
             RerSetup::System => {
                 if cfg!(target_os = "linux") {
                     PathBuf::from("/etc/rerman/config.toml")
@@ -142,7 +375,8 @@ impl Rer {
             .parse()
             .unwrap_or(langid!("en-US"));
         let i18n = I18N::new();
-        let cli = Cli::parse();
+        let args = std::env::args().collect::<Vec<_>>();
+        let mut cli = Cli::parse_localized(&args, &i18n, &lang_id, true);
         let setup = if let Some(true) = cli.system {
             RerSetup::System
         } else if let Some(true) = cli.user {
@@ -190,158 +424,481 @@ impl Rer {
             RerSetup::Local => (current_dir()?).join(".rerman").join("config.toml"),
             RerSetup::Custom { ref config_file } => config_file.to_owned(),
         };
-        let config = tokio::fs::read(config_file).await.or_else(|_| {
-            println!(
-                "{}",
-                i18n.format_msg_or_log(&lang_id, "error-read-config-file", None)
-            );
-            toml::to_string(&Config::default()).map(|v| v.as_bytes().to_vec())
-        })?;
-        let config: Config = toml::from_str(&String::from_utf8(config)?).unwrap_or_else(|_| {
+        let config = match tokio::fs::read_to_string(&config_file).await {
+            Ok(config) => match config::migrate(&config) {
+                Some((migrated, migration)) => {
+                    match config::save_migrated(&config_file, &config, &migrated, &migration).await
+                    {
+                        Ok(backup_file) => eprintln!(
+                            "{}",
+                            i18n.format_msg_or_log(
+                                &lang_id,
+                                "info-config-migrated",
+                                Some(migration_params(&config_file, &migration, &backup_file))
+                            )
+                        ),
+                        Err(err) => warn!("saving the migrated config failed: {:?}", err),
+                    }
+                    migrated
+                }
+                None => config,
+            },
+            Err(_) => {
+                println!(
+                    "{}",
+                    i18n.format_msg_or_log(&lang_id, "error-read-config-file", None)
+                );
+                toml::to_string(&Config::default())?
+            }
+        };
+        let config: Config = toml::from_str(&config).unwrap_or_else(|_| {
             println!(
                 "{}",
                 i18n.format_msg_or_log(&lang_id, "error-read-config-file", None)
             );
             Config::default()
         });
+        if let Commands::External(ref external) = cli.commands {
+            let args = expand_alias(&args, external, config.aliases.as_ref());
+            cli = Cli::parse_localized(&args, &i18n, &lang_id, false);
+        }
+        let painter = Painter::new(cli.color, config.theme.as_ref());
         Ok(Rer {
             cli,
             setup,
             config,
             i18n,
             lang_id,
+            painter,
+            api: OnceLock::new(),
         })
     }
 
-    pub async fn run(&self) -> anyhow::Result<()> {
-        match &self.cli.commands {
-            Commands::Clone { r#type: ty, target } => {
-                match ty.as_str() {
-                    "git" => {
-                        let url = GitUrl::parse(target)?;
-                        let git = Git::default();
-                        git.clone(
-                            target,
-                            self.repo_dir()?
-                                .join("git")
-                                .join(url.host())
-                                .join(url.username())
-                                .join({
-                                    let path = url.path();
-                                    let path = path.strip_prefix('/').unwrap_or(path);
-                                    let path = path.strip_suffix(".git").unwrap_or(path);
-                                    path
-                                })
-                                .to_string_lossy(),
-                        )
-                        .await?;
+    pub fn cancellable(&self) -> bool {
+        self.cli.commands.cancellable()
+    }
+
+    pub fn report_cancelled(&self) {
+        eprintln!(
+            "{}",
+            self.painter.paint(
+                Paint::Error,
+                self.i18n
+                    .format_msg_or_log(&self.lang_id, "error-cancelled", None)
+            )
+        );
+    }
+
+    pub fn report_error(&self, err: &anyhow::Error) {
+        eprintln!(
+            "{}",
+            self.painter
+                .paint(Paint::Error, format!("Error: {:?}", err))
+        );
+    }
+
+    /// Prints a hint if a newer release exists, checking at most once a day.
+    pub async fn notify_update(&self) {
+        if !self.config.update_check.unwrap_or(true)
+            || matches!(self.cli.commands, Commands::SelfUpdate { .. })
+        {
+            return;
+        }
+        let latest = match self.data_dir() {
+            Ok(data_dir) => {
+                release::latest_version_cached(
+                    data_dir.join("update-check"),
+                    self.config
+                        .update_url
+                        .as_deref()
+                        .unwrap_or(DEFAULT_RELEASE_URL),
+                )
+                .await
+            }
+            Err(err) => Err(err),
+        };
+        match latest {
+            Ok(latest) if latest > current_version() => eprintln!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-update-hint",
+                        Some(vec![
+                            ("current".to_string(), current_version().to_string()),
+                            ("latest".to_string(), latest.to_string()),
+                        ])
+                    )
+                )
+            ),
+            Ok(_) => {}
+            Err(err) => debug!("update check failed: {:?}", err),
+        }
+    }
+
+    fn dir_of(&self, repo: &RepoTableItem) -> anyhow::Result<PathBuf> {
+        let name = repo
+            .path
+            .strip_prefix(&repo.owner)
+            .and_then(|name| name.strip_prefix('/'))
+            .unwrap_or(&repo.path);
+        self.path_of_repo(&repo.ty, &repo.hostname, &repo.owner, name)
+    }
+
+    fn render_table(&self, mut table: tabled::Table) -> String {
+        match self.config.table_style.unwrap_or_default() {
+            TableStyle::Ascii => table.with(tabled::settings::Style::ascii()),
+            TableStyle::Rounded => table.with(tabled::settings::Style::rounded()),
+            TableStyle::Markdown => table.with(tabled::settings::Style::markdown()),
+            TableStyle::Borderless => table.with(tabled::settings::Style::blank()),
+        };
+        table
+            .with(
+                tabled::settings::Modify::new(tabled::settings::object::Rows::first()).with(
+                    tabled::settings::Format::content(|header| {
+                        self.painter.paint(Paint::Header, header)
+                    }),
+                ),
+            )
+            .to_string()
+    }
+
+    fn render_repos(&self, list: &[RepoTableItem], columns: &[RepoColumn]) -> String {
+        let mut builder = tabled::builder::Builder::new();
+        builder.push_record(columns.iter().map(RepoColumn::name));
+        for item in list {
+            builder.push_record(columns.iter().map(|column| item.column(*column)));
+            for sub in &item.subrepos {
+                builder.push_record(columns.iter().map(|column| match column {
+                    RepoColumn::Path => format!("  └ {}", sub),
+                    _ => String::new(),
+                }));
+            }
+        }
+        self.render_table(builder.build())
+    }
+
+    /// The repositories matching `filter`, found by walking the roots.
+    async fn scan_repos(&self, filter: &RepoFilter) -> anyhow::Result<Vec<RepoTableItem>> {
+        let repos = self.walk_repos().await?;
+        self.filter_repos(repos, filter).await
+    }
+
+    /// The repositories of `repos` matching `filter`.
+    async fn filter_repos(
+        &self,
+        repos: Vec<RepoTableItem>,
+        filter: &RepoFilter,
+    ) -> anyhow::Result<Vec<RepoTableItem>> {
+        let script = filter
+            .filter_script
+            .as_deref()
+            .map(|file| Script::load(&layout::expand_home(file)))
+            .transpose()?;
+        let query = filter
+            .filter_where
+            .as_deref()
+            .map(Query::parse)
+            .transpose()?;
+        let members = match filter.group {
+            Some(ref group) => Some(Box::pin(self.group_members(group, &repos)).await?),
+            None => None,
+        };
+        let mut list = vec![];
+        for item in repos {
+            if let Some(ref r#type) = filter.filter_type {
+                if !item.ty.contains(r#type) {
+                    continue;
+                }
+            }
+            if let Some(ref hostname) = filter.filter_hostname {
+                if !item.hostname.contains(hostname) {
+                    continue;
+                }
+            }
+            if let Some(ref filter_path) = filter.filter_path {
+                if !item.path.contains(filter_path) {
+                    continue;
+                }
+            }
+            if let Some(ref filter_owner) = filter.filter_owner {
+                if !item.owner.contains(filter_owner) {
+                    continue;
+                }
+            }
+            if filter.filter_devcontainer && !item.devcontainer {
+                continue;
+            }
+            if let Some(ref filter_lang) = filter.filter_lang {
+                if !item
+                    .language
+                    .as_ref()
+                    .is_some_and(|language| language.eq_ignore_ascii_case(filter_lang))
+                {
+                    continue;
+                }
+            }
+            if let Some(ref filter_user) = filter.filter_user {
+                if !item
+                    .user
+                    .as_ref()
+                    .is_some_and(|user| user.contains(filter_user))
+                {
+                    continue;
+                }
+            }
+            if let Some(ref members) = members {
+                if !members.iter().any(|member| {
+                    member.ty == item.ty
+                        && member.hostname == item.hostname
+                        && member.path == item.path
+                }) {
+                    continue;
+                }
+            }
+            if script.is_some() || query.is_some() {
+                let repo_dir = self.dir_of(&item)?;
+                if let Some(ref script) = script {
+                    if !script.matches(&item, &repo_dir)? {
+                        continue;
                     }
-                    _ => {
-                        todo!("more repository type")
+                }
+                if let Some(ref query) = query {
+                    if !self.query_matches(query, &item, &repo_dir).await? {
+                        continue;
                     }
                 }
-                Ok(())
             }
-            Commands::Setup => {
-                let config_file = self.config_file()?;
-                let config_dir = config_file.parent().ok_or_else(|| {
-                    anyhow!(
-                        "{}",
-                        self.i18n.format_msg_or_log(
-                            &self.lang_id,
-                            "error-invalid-config-dir",
-                            Some(vec![(
-                                "dir".to_string(),
-                                config_file.join("..").to_string_lossy().to_string()
-                            )])
-                        )
-                    )
-                })?;
-                if config_dir.exists() && !config_dir.is_dir() {
-                    Err(anyhow!(
+            list.push(item);
+        }
+        Ok(list)
+    }
+
+    /// Whether `item` matches a `--where` query, looking up the fields that need
+    /// git or a walk of the repository only if the query uses them.
+    async fn query_matches(
+        &self,
+        query: &Query,
+        item: &RepoTableItem,
+        dir: &Path,
+    ) -> anyhow::Result<bool> {
+        let mut fields = rhai::serde::to_dynamic(item)
+            .map_err(|err| anyhow!("{}", err))?
+            .try_cast::<rhai::Map>()
+            .ok_or_else(|| anyhow!("repository is not an object"))?;
+        if query.uses("branch") || query.uses("dirty") {
+            // repositories without git status have no branch and no changes
+            let status = match item.ty.as_str() {
+                "git" => self.git().status(dir).await.ok(),
+                _ => None,
+            };
+            let (branch, dirty) = status
+                .map(|status| (status.branch, status.dirty))
+                .unwrap_or_default();
+            fields.insert("branch".into(), branch.into());
+            fields.insert("dirty".into(), dirty.into());
+        }
+        if query.uses("size") {
+            let size = fs::dir_size(dir);
+            fields.insert("size".into(), (size as i64).into());
+        }
+        query.matches(fields, dir)
+    }
+
+    /// The repository in `dir`, if it is inside one of the roots.
+    async fn repo_at(&self, dir: &Path) -> anyhow::Result<Option<RepoTableItem>> {
+        for (ty, root) in self.layout()?.roots()? {
+            let Ok(rest) = dir.strip_prefix(&root) else {
+                continue;
+            };
+            let mut components = rest.components();
+            let Some(host) = components.next() else {
+                continue;
+            };
+            let host = host.as_os_str().to_string_lossy();
+            let owner = layout::owner_of(components.as_path());
+            let repo_path = components.as_path().to_string_lossy();
+            return Ok(Some(repo_item(&ty, &host, &owner, &repo_path, dir).await));
+        }
+        Ok(None)
+    }
+
+    /// Fails with a hint to use sudo or another level if the roots or the data
+    /// directory cannot be written to, before a command gets halfway.
+    fn ensure_writable(&self) -> anyhow::Result<()> {
+        let mut dirs = vec![self.repo_dir()?, self.data_dir()?];
+        dirs.extend(self.layout()?.roots()?.into_iter().map(|(_, root)| root));
+        // the migrated config is saved next to a backup of it
+        if let Commands::Config {
+            commands: Some(ConfigCommands::Migrate),
+            ..
+        } = self.cli.commands
+        {
+            let config_file = std::path::absolute(self.config_file()?)?;
+            dirs.extend(config_file.parent().map(Path::to_path_buf));
+        }
+        if let Some(dir) = dirs.iter().find(|dir| !fs::is_writable(dir)) {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    match self.setup {
+                        RerSetup::System => "error-read-only-system-root",
+                        _ => "error-read-only-root",
+                    },
+                    Some(vec![("dir".to_string(), dir.to_string_lossy().to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn run(&self) -> anyhow::Result<()> {
+        if self.cli.commands.writes() {
+            self.ensure_writable()?;
+        }
+        match &self.cli.commands {
+            Commands::Clone {
+                r#type: ty,
+                origin_url,
+                target,
+                json,
+            } => self.clone(ty, origin_url.as_deref(), target, *json).await,
+            Commands::Discover {
+                // the default, only there to be explicit
+                starred: _,
+                watched,
+                all,
+                host,
+            } => self.discover(host, *watched, *all).await,
+            Commands::Adopt { url, path } => {
+                let path = PathBuf::from(path);
+                if !path.join(".git").exists() {
+                    return Err(anyhow!(
                         "{}",
                         self.i18n.format_msg_or_log(
                             &self.lang_id,
-                            "error-invalid-config-dir",
+                            "error-not-a-git-repo",
                             Some(vec![(
                                 "dir".to_string(),
-                                config_dir.to_string_lossy().to_string()
+                                path.to_string_lossy().to_string()
                             )])
                         )
-                    ))?
+                    ));
                 }
-                if !config_dir.exists() {
-                    tokio::fs::create_dir_all(config_dir).await?;
+                let dest = self.path_of_git_url(&GitUrl::parse(url)?)?;
+                fs::move_dir(&path, &dest).await?;
+                let git = self.git();
+                if !git
+                    .remotes(&dest)
+                    .await?
+                    .iter()
+                    .any(|remote| remote == "origin")
+                {
+                    git.output(&dest, &["remote", "add", "origin", url]).await?;
                 }
-
-                let repo_dir = self.repo_dir()?;
-                if repo_dir.exists() && !repo_dir.is_dir() {
-                    Err(anyhow!(
-                        "{}",
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
                         self.i18n.format_msg_or_log(
                             &self.lang_id,
-                            "error-invalid-repo-dir",
+                            "info-repo-moved",
                             Some(vec![(
                                 "dir".to_string(),
-                                repo_dir.to_string_lossy().to_string()
+                                dest.to_string_lossy().to_string()
                             )])
                         )
-                    ))?
-                }
-                if !repo_dir.exists() {
-                    tokio::fs::create_dir_all(repo_dir).await?;
-                }
-
-                tokio::fs::write(
-                    &config_file,
-                    include_str!("../assets/config.toml").as_bytes(),
-                )
-                .await?;
-                println!(
-                    "{}",
-                    self.i18n.format_msg_or_log(
-                        &self.lang_id,
-                        "info-setup-completed",
-                        Some(vec![(
-                            "file".to_string(),
-                            config_file.to_string_lossy().to_string()
-                        )])
                     )
                 );
                 Ok(())
             }
-            Commands::Open { with, target } => {
-                let open_with = with.to_owned().ok_or(()).or_else(|_| {
-                    self.default_open_with().ok_or(anyhow!(
-                        "{}",
-                        self.i18n.format_msg_or_log(
-                            &self.lang_id,
-                            "error-no-default-open-with",
-                            None
-                        )
-                    ))
-                })?;
-                for type_dir in std::fs::read_dir(self.repo_dir()?)? {
-                    for host_dir in std::fs::read_dir(type_dir?.path())? {
-                        let target_dir = host_dir?.path().join(target);
-                        if target_dir.exists() && target_dir.join(".git").exists() {
-                            tokio::process::Command::new(open_with)
-                                .arg(target_dir.to_string_lossy().to_string())
-                                .stdout(Stdio::inherit())
-                                .spawn()?
-                                .wait()
+            Commands::Update {
+                filter,
+                json,
+                relocate,
+                porcelain,
+            } => self.update(filter, *json, *relocate, *porcelain).await,
+            Commands::Init { force } => self.init(*force).await,
+            Commands::Open {
+                devcontainer,
+                with,
+                target,
+            } => {
+                if *devcontainer {
+                    self.open_devcontainer(target).await
+                } else {
+                    self.open(with.as_deref(), target).await
+                }
+            }
+            Commands::Enter { with, target } => self.enter(with.as_deref(), target).await,
+            Commands::Browse {
+                commit,
+                pr,
+                file,
+                line,
+                copy,
+                target,
+            } => {
+                self.browse(
+                    target,
+                    commit.as_deref(),
+                    *pr,
+                    file.as_deref(),
+                    *line,
+                    *copy,
+                )
+                .await
+            }
+            Commands::Copy { url, target, .. } => self.copy(target, *url).await,
+            Commands::Config {
+                commands: Some(ConfigCommands::Migrate),
+                ..
+            } => {
+                let config_file = self.config_file()?;
+                let config = tokio::fs::read_to_string(&config_file).await?;
+                match config::migrate(&config) {
+                    Some((migrated, migration)) => {
+                        let backup_file =
+                            config::save_migrated(&config_file, &config, &migrated, &migration)
                                 .await?;
-                            return Ok(());
-                        }
+                        println!(
+                            "{}",
+                            self.painter.paint(
+                                Paint::Info,
+                                self.i18n.format_msg_or_log(
+                                    &self.lang_id,
+                                    "info-config-migrated",
+                                    Some(migration_params(&config_file, &migration, &backup_file))
+                                )
+                            )
+                        );
                     }
+                    None => println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-config-current",
+                                Some(vec![
+                                    (
+                                        "file".to_string(),
+                                        config_file.to_string_lossy().to_string()
+                                    ),
+                                    (
+                                        "version".to_string(),
+                                        self.config.version.unwrap_or_default().to_string()
+                                    ),
+                                ])
+                            )
+                        )
+                    ),
                 }
-                Err(anyhow!(
-                    "{}",
-                    self.i18n
-                        .format_msg_or_log(&self.lang_id, "error-target-not-found", None)
-                ))
+                Ok(())
             }
-            Commands::Config { edit, with } => {
+            Commands::Config { edit, with, .. } => {
                 if *edit {
                     let with_editor = with
                         .to_owned()
@@ -377,77 +934,347 @@ impl Rer {
             Commands::Create {
                 r#type: ty,
                 hostname,
+                from,
+                publish,
+                template,
+                var,
+                json,
                 target,
-            } => match ty.as_str() {
-                "git" => {
-                    Git::default()
-                        .init(
-                            self.path_of_repo(ty, hostname, "", target)?
-                                .to_string_lossy(),
+            } => match (ty.as_str(), from) {
+                ("git", Some(from)) => {
+                    self.create_from(
+                        hostname.as_deref(),
+                        target,
+                        Path::new(from),
+                        *publish,
+                        *json,
+                    )
+                    .await
+                }
+                ("git", None) => {
+                    let start = Instant::now();
+                    let (target_host, target) = match hostname {
+                        Some(_) => (None, target.as_str()),
+                        None => target::split_host(target),
+                    };
+                    let hostname = hostname
+                        .as_deref()
+                        .or(target_host)
+                        .or(self.config.default_host.as_deref())
+                        .unwrap_or("localhost");
+                    let target = self.owned_target(hostname, target).await;
+                    let (owner, name) = layout::split_path(&target);
+                    let path = self.path_of_repo(ty, hostname, owner, name)?;
+                    // asked for before anything is created
+                    let template = match template {
+                        Some(template) => Some(
+                            self.prepare_template(template, var, hostname, &target)
+                                .await?,
+                        ),
+                        None => None,
+                    };
+                    let (status, stderr) = if *json {
+                        self.git()
+                            .run_captured(&["init", &path.to_string_lossy()])
+                            .await?
+                    } else {
+                        // the output of git is shown, so stderr is not kept
+                        (
+                            self.git().init(path.to_string_lossy()).await?,
+                            String::new(),
                         )
-                        .await?;
+                    };
+                    let outcome = Outcome::new(&path, start).with_exit(status, &stderr);
+                    if outcome.success {
+                        if let Some(template) = &template {
+                            self.fill_from_template(template, &path, *json).await?;
+                        }
+                        self.record_cloned_by(&path).await;
+                        self.run_hook(hooks::Hook::PostCreate, &path).await;
+                    }
+                    if *json {
+                        println!("{}", serde_json::to_string(&outcome)?);
+                    }
+                    if !outcome.success {
+                        let error = match outcome.exit_code {
+                            Some(code) => format!("exit status: {}", code),
+                            None => outcome.stderr_tail,
+                        };
+                        return Err(anyhow!(
+                            "{}",
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-repo-create-failed",
+                                Some(vec![
+                                    ("repo".to_string(), target.to_owned()),
+                                    ("error".to_string(), error),
+                                ])
+                            )
+                        ));
+                    }
                     Ok(())
                 }
-                _ => {
-                    todo!("more repository type")
-                }
+                (ty, _) => Err(anyhow!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-create-unsupported-type",
+                        Some(vec![("type".to_string(), ty.to_string())])
+                    )
+                )),
             },
             Commands::List {
-                filter_type,
-                filter_hostname,
-                filter_path,
+                filter,
                 json,
+                columns,
+                long,
+                porcelain,
+                format,
+                no_cache,
             } => {
-                let mut list = vec![];
-                let repo_dir_path = self.repo_dir()?;
-                for type_dir in std::fs::read_dir(self.repo_dir()?)? {
-                    let type_dir_path = type_dir?.path();
-                    let ty = type_dir_path
-                        .strip_prefix(&repo_dir_path)?
-                        .to_string_lossy()
-                        .to_string();
-                    if let Some(r#type) = filter_type {
-                        if !ty.contains(r#type) {
-                            continue;
-                        }
+                let repos = if *no_cache {
+                    self.walk_repos().await?
+                } else {
+                    self.indexed_repos().await?
+                };
+                let list = self.filter_repos(repos, filter).await?;
+                if let Some(format) = format {
+                    for item in &list {
+                        let fields = RepoColumn::value_variants()
+                            .iter()
+                            .map(|column| (column.name(), item.column(*column)))
+                            .collect::<Vec<_>>();
+                        println!("{}", template::render(format, &fields)?);
                     }
-                    for host_dir in std::fs::read_dir(&type_dir_path)? {
-                        let host_dir_path = host_dir?.path();
-                        let host = host_dir_path
-                            .strip_prefix(&type_dir_path)?
-                            .to_string_lossy()
-                            .to_string();
-                        if let Some(hostname) = filter_hostname {
-                            if !host.contains(hostname) {
-                                continue;
-                            }
-                        }
-                        for repo_dir in filter_git_paths_recursively(&host_dir_path).await? {
-                            let repo_path = repo_dir
-                                .strip_prefix(&host_dir_path)?
-                                .to_string_lossy()
-                                .to_string();
-                            if let Some(filter_path) = filter_path {
-                                if !repo_path.contains(filter_path) {
-                                    continue;
-                                }
-                            }
-
-                            list.push(RepoTableItem {
-                                path: repo_path.to_owned(),
-                                ty: ty.to_owned(),
-                                hostname: host.to_owned(),
-                            });
-                        }
+                } else if let Some(PorcelainVersion::V1) = porcelain {
+                    for item in &list {
+                        println!(
+                            "{}",
+                            porcelain::record([
+                                item.ty.as_str(),
+                                item.hostname.as_str(),
+                                item.path.as_str()
+                            ])
+                        );
                     }
-                }
-                if *json {
+                } else if *json {
                     println!("{}", serde_json::to_string(&list)?);
                 } else {
-                    println!("{}", tabled::Table::new(list));
+                    let columns = match columns {
+                        Some(columns) => columns.as_slice(),
+                        None if *long => RepoColumn::LONG,
+                        None => RepoColumn::DEFAULT,
+                    };
+                    println!("{}", self.render_repos(&list, columns));
                 }
                 Ok(())
             }
+            Commands::Search { query } => {
+                let query = query.to_lowercase();
+                let list = self
+                    .scan_repos(&RepoFilter::default())
+                    .await?
+                    .into_iter()
+                    .filter(|repo| {
+                        repo.path.to_lowercase().contains(&query)
+                            || repo
+                                .description
+                                .as_ref()
+                                .is_some_and(|d| d.to_lowercase().contains(&query))
+                            || repo
+                                .topics
+                                .iter()
+                                .any(|t| t.to_lowercase().contains(&query))
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", self.render_repos(&list, RepoColumn::LONG));
+                Ok(())
+            }
+            Commands::RefreshMeta { filter } => self.refresh_meta(filter).await,
+            Commands::Status {
+                filter,
+                porcelain,
+                format,
+            } => {
+                let git = self.git();
+                let mut list = vec![];
+                for repo in self.scan_repos(filter).await? {
+                    if repo.ty != "git" {
+                        continue;
+                    }
+                    let status = git.status(self.dir_of(&repo)?).await?;
+                    if let Some(format) = format {
+                        let fields = [
+                            ("path", repo.path),
+                            ("type", repo.ty),
+                            ("hostname", repo.hostname),
+                            ("branch", status.branch),
+                            (
+                                "status",
+                                if status.dirty { "dirty" } else { "clean" }.to_string(),
+                            ),
+                        ];
+                        println!("{}", template::render(format, &fields)?);
+                        continue;
+                    }
+                    if let Some(PorcelainVersion::V1) = porcelain {
+                        println!(
+                            "{}",
+                            porcelain::record([
+                                repo.ty.as_str(),
+                                repo.hostname.as_str(),
+                                repo.path.as_str(),
+                                status.branch.as_str(),
+                                if status.dirty { "dirty" } else { "clean" },
+                            ])
+                        );
+                        continue;
+                    }
+                    list.push(RepoStatusItem {
+                        path: repo.path,
+                        ty: repo.ty,
+                        hostname: repo.hostname,
+                        branch: status.branch,
+                        status: if status.dirty {
+                            self.painter.paint(
+                                Paint::Dirty,
+                                self.i18n
+                                    .format_msg_or_log(&self.lang_id, "status-dirty", None),
+                            )
+                        } else {
+                            self.painter.paint(
+                                Paint::Clean,
+                                self.i18n
+                                    .format_msg_or_log(&self.lang_id, "status-clean", None),
+                            )
+                        },
+                    });
+                }
+                if porcelain.is_none() && format.is_none() {
+                    println!("{}", self.render_table(tabled::Table::new(list)));
+                }
+                Ok(())
+            }
+            Commands::Exec {
+                output,
+                jobs,
+                filter,
+                command,
+            } => self.exec(filter, *output, *jobs, command).await,
+            Commands::SelfUpdate { check } => {
+                let release = Release::fetch(
+                    self.config
+                        .update_url
+                        .as_deref()
+                        .unwrap_or(DEFAULT_RELEASE_URL),
+                )
+                .await?;
+                let version_params = Some(vec![
+                    ("current".to_string(), current_version().to_string()),
+                    ("latest".to_string(), release.version()?.to_string()),
+                ]);
+                if !release.is_newer()? {
+                    println!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-up-to-date",
+                            version_params
+                        )
+                    );
+                    return Ok(());
+                }
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-update-available",
+                            version_params.to_owned()
+                        )
+                    )
+                );
+                if *check {
+                    return Ok(());
+                }
+                let no_asset = || {
+                    anyhow!(
+                        "{}",
+                        self.i18n
+                            .format_msg_or_log(&self.lang_id, "error-no-release-asset", None)
+                    )
+                };
+                let binary = release.binary_asset().ok_or_else(no_asset)?;
+                let checksum = release.checksum_asset(binary).ok_or_else(no_asset)?;
+                let data = http::download(&binary.browser_download_url).await?;
+                release::verify_sha256(
+                    &data,
+                    &String::from_utf8(http::download(&checksum.browser_download_url).await?)?,
+                )?;
+                release::replace_binary(&std::env::current_exe()?, &data).await?;
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n
+                            .format_msg_or_log(&self.lang_id, "info-updated", version_params)
+                    )
+                );
+                Ok(())
+            }
+            Commands::Mirror { commands } => self.mirror(commands).await,
+            Commands::Remotes { commands } => self.remotes(commands).await,
+            Commands::Manifest { commands } => self.manifest(commands).await,
+            Commands::Audit { commands } => self.audit(commands).await,
+            Commands::DiffManifest {
+                format,
+                json,
+                filter,
+                file,
+            } => self.diff_manifest(file, *format, filter, *json).await,
+            Commands::Resume => self.resume().await,
+            Commands::Cache { commands } => self.cache(commands).await,
+            Commands::Timeline {
+                since,
+                author,
+                json,
+                filter,
+            } => self.timeline(since, author.as_deref(), *json, filter).await,
+            Commands::Branches { commands, filter } => {
+                self.branches(commands.as_ref(), filter).await
+            }
+            Commands::Layout {
+                commands: LayoutCommands::Migrate { filter, apply },
+            } => self.migrate_layout(filter, *apply).await,
+            Commands::Group { commands } => self.group(commands).await,
+            Commands::Watch => self.watch().await,
+            Commands::Jobs { commands } => self.jobs(commands).await,
+            Commands::Template { commands } => self.templates(commands).await,
+            Commands::Subrepo { commands } => self.subrepo(commands).await,
+            Commands::Apply {
+                filter,
+                script,
+                branch,
+                message,
+                pr,
+            } => {
+                self.apply(filter, script, branch, message.as_deref(), *pr)
+                    .await
+            }
+            Commands::CommitAll {
+                filter,
+                message,
+                add,
+            } => self.commit_all(filter, message, add).await,
+            Commands::Push {
+                filter,
+                force_with_lease,
+            } => self.push(filter, *force_with_lease).await,
+            Commands::SyncFork { target, filter } => {
+                self.sync_fork(target.as_deref(), filter).await
+            }
+            Commands::External(_) => unreachable!("aliases are expanded while parsing"),
             Commands::Debug { commands } => match commands {
                 DebugCommands::Locale => {
                     println!(
@@ -464,3 +1291,79 @@ impl Rer {
         }
     }
 }
+
+/// The parameters of the `info-config-migrated` message.
+fn migration_params(
+    config_file: &std::path::Path,
+    migration: &config::Migration,
+    backup_file: &std::path::Path,
+) -> Vec<(String, String)> {
+    vec![
+        (
+            "file".to_string(),
+            config_file.to_string_lossy().to_string(),
+        ),
+        ("from".to_string(), migration.from.to_string()),
+        ("to".to_string(), config::CONFIG_VERSION.to_string()),
+        ("changes".to_string(), migration.changes.join(", ")),
+        (
+            "backup".to_string(),
+            backup_file.to_string_lossy().to_string(),
+        ),
+    ]
+}
+
+/// Expands the alias named by the external subcommand `external`, the tail of
+/// `args`, like git aliases do. Aliases may refer to other aliases.
+fn expand_alias(
+    args: &[String],
+    external: &[String],
+    aliases: Option<&BTreeMap<String, String>>,
+) -> Vec<String> {
+    let mut prefix = args[..args.len() - external.len()].to_vec();
+    let mut rest = external.to_vec();
+    let mut seen = HashSet::new();
+    while let Some(expansion) = rest
+        .first()
+        .filter(|name| seen.insert(name.to_string()))
+        .and_then(|name| aliases?.get(name))
+    {
+        match shell_words::split(expansion) {
+            Ok(words) => {
+                rest.splice(..1, words);
+            }
+            Err(err) => {
+                warn!("invalid alias '{}': {}", rest[0], err);
+                break;
+            }
+        }
+    }
+    prefix.append(&mut rest);
+    prefix
+}
+
+async fn repo_item(
+    ty: &str,
+    host: &str,
+    owner: &str,
+    repo_path: &str,
+    repo_dir: &Path,
+) -> RepoTableItem {
+    let meta = RepoMeta::load(repo_dir).await.unwrap_or_else(|err| {
+        warn!("invalid metadata of {}: {:?}", repo_path, err);
+        RepoMeta::default()
+    });
+    RepoTableItem {
+        path: repo_path.to_owned(),
+        ty: ty.to_owned(),
+        hostname: host.to_owned(),
+        owner: owner.to_owned(),
+        branch: git::head_branch(repo_dir),
+        user: meta.cloned_by,
+        description: meta.description,
+        topics: meta.topics,
+        devcontainer: devcontainer::detect(repo_dir),
+        language: toolchain::detect(repo_dir).map(str::to_string),
+        subrepos: meta.subrepos,
+    }
+}
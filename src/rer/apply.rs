@@ -0,0 +1,237 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::{
+    cli::RepoFilter,
+    forge::{Forge, PullRequest},
+    git::{Git, GitUrl},
+    layout,
+    style::Paint,
+};
+
+use super::Rer;
+
+enum ApplyResult {
+    /// The script changed nothing, the branch was removed again.
+    Unchanged,
+    Committed,
+    PullRequest(String),
+}
+
+/// What `apply` does in each repository.
+struct Change<'a> {
+    /// A shell command or rhai script as run by hooks.
+    command: String,
+    branch: &'a str,
+    message: String,
+    pr: bool,
+}
+
+impl Rer {
+    /// Runs `script` on a new branch of every matching clean git repository and
+    /// commits what it changed, pushing the branch and opening a pull request
+    /// with `pr`. Repositories are left on the branch they were on.
+    pub(super) async fn apply(
+        &self,
+        filter: &RepoFilter,
+        script: &str,
+        branch: &str,
+        message: Option<&str>,
+        pr: bool,
+    ) -> anyhow::Result<()> {
+        let file = layout::expand_home(script);
+        // files are run from the repositories, so they need an absolute path
+        let command = if file.is_file() {
+            let file = std::path::absolute(&file)?.to_string_lossy().to_string();
+            if file.ends_with(".rhai") {
+                file
+            } else {
+                shell_words::quote(&file).to_string()
+            }
+        } else {
+            script.to_string()
+        };
+        let change = Change {
+            command,
+            branch,
+            message: message
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Apply {}", script)),
+            pr,
+        };
+        let git = self.git();
+        let mut pull_requests = vec![];
+        let mut failed = 0;
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            let params = |key: &str, value: &str| {
+                Some(vec![
+                    ("repo".to_string(), repo.path.to_owned()),
+                    (key.to_string(), value.to_string()),
+                ])
+            };
+            match self.apply_repo(&git, &dir, &change).await {
+                Ok(ApplyResult::Unchanged) => println!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-apply-unchanged",
+                        params("branch", branch)
+                    )
+                ),
+                Ok(ApplyResult::Committed) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-apply-committed",
+                            params("branch", branch)
+                        )
+                    )
+                ),
+                Ok(ApplyResult::PullRequest(url)) => {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-apply-pr-opened",
+                                params("url", &url)
+                            )
+                        )
+                    );
+                    pull_requests.push(url);
+                }
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-apply-failed",
+                                params("error", &err.to_string())
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        if !pull_requests.is_empty() {
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Header,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-apply-prs",
+                        Some(vec![("count".to_string(), pull_requests.len().to_string())])
+                    )
+                )
+            );
+            for url in pull_requests {
+                println!("{}", url);
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    async fn apply_repo(
+        &self,
+        git: &Git,
+        dir: &Path,
+        change: &Change<'_>,
+    ) -> anyhow::Result<ApplyResult> {
+        if git.status(dir).await?.dirty {
+            return Err(anyhow!(
+                "{}",
+                self.i18n
+                    .format_msg_or_log(&self.lang_id, "error-apply-dirty", None)
+            ));
+        }
+        // a missing token fails before anything is changed
+        let forge = if change.pr {
+            let url = GitUrl::parse(git.remote_url(dir, "origin").await?)?;
+            let (host, owner, name) = url.canonical();
+            let (forge, token) = self.forge_with_token(&host)?;
+            Some((forge, token, host, format!("{}/{}", owner, name)))
+        } else {
+            None
+        };
+        let base = git.current_branch(dir).await?;
+        let before = git.head(dir).await;
+        git.output(dir, &["checkout", "--quiet", "-b", change.branch])
+            .await?;
+        let result = self.apply_on_branch(git, dir, change, &base, forge).await;
+        // a branch with a commit is kept, also when pushing it failed
+        let committed = git.head(dir).await != before;
+        if !committed {
+            // the repository was clean, so whatever is left is the script's
+            git.output(dir, &["reset", "--quiet", "--hard"]).await?;
+            git.output(dir, &["clean", "--quiet", "-fd"]).await?;
+        }
+        git.output(dir, &["checkout", "--quiet", &base]).await?;
+        if !committed {
+            git.output(dir, &["branch", "--quiet", "-D", change.branch])
+                .await?;
+        }
+        result
+    }
+
+    async fn apply_on_branch(
+        &self,
+        git: &Git,
+        dir: &Path,
+        change: &Change<'_>,
+        base: &str,
+        forge: Option<(Forge, String, String, String)>,
+    ) -> anyhow::Result<ApplyResult> {
+        self.run_hook_command(&change.command, dir).await?;
+        if !git.status(dir).await?.dirty {
+            return Ok(ApplyResult::Unchanged);
+        }
+        git.output(dir, &["add", "--all"]).await?;
+        git.output(dir, &["commit", "--quiet", "-m", &change.message])
+            .await?;
+        let Some((forge, token, host, project)) = forge else {
+            return Ok(ApplyResult::Committed);
+        };
+        git.output(
+            dir,
+            &["push", "--quiet", "--set-upstream", "origin", change.branch],
+        )
+        .await?;
+        let title = change.message.lines().next().unwrap_or_default();
+        let url = forge
+            .open_pull_request(
+                self.api()?,
+                &host,
+                &project,
+                &token,
+                &PullRequest {
+                    branch: change.branch,
+                    base,
+                    title,
+                },
+            )
+            .await?;
+        Ok(ApplyResult::PullRequest(url))
+    }
+}
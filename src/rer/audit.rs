@@ -0,0 +1,518 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use tabled::Tabled;
+
+use crate::{cli::AuditCommands, deps, fs, git::Git, secrets, style::Paint};
+
+use super::Rer;
+
+#[derive(Tabled, serde::Serialize, Default)]
+struct SignatureItem {
+    path: String,
+    hostname: String,
+    branch: String,
+    commits: usize,
+    /// Commits without a signature.
+    unsigned: usize,
+    /// Commits signed with keys that are not known, expired or revoked.
+    unverified: usize,
+    /// Commits whose signature does not match.
+    bad: usize,
+    tags: usize,
+    unsigned_tags: usize,
+    unverified_tags: usize,
+}
+
+/// A line of a repository that looks like it holds a secret, which is not
+/// reported itself.
+#[derive(Tabled, serde::Serialize)]
+struct SecretItem {
+    path: String,
+    hostname: String,
+    file: String,
+    line: usize,
+    rule: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GitleaksFinding {
+    #[serde(rename = "RuleID")]
+    rule_id: String,
+    file: String,
+    start_line: usize,
+}
+
+/// A package locked by the repositories, with its versions.
+#[derive(Tabled)]
+struct DependencyItem {
+    ecosystem: &'static str,
+    name: String,
+    versions: String,
+    repos: usize,
+}
+
+#[derive(serde::Serialize)]
+struct DependencyInventory {
+    ecosystem: &'static str,
+    name: String,
+    versions: BTreeSet<String>,
+    repos: BTreeSet<String>,
+}
+
+/// A repository locking a package asked for with `--contains`.
+#[derive(Tabled, serde::Serialize)]
+struct DependencyUse {
+    path: String,
+    hostname: String,
+    file: String,
+    ecosystem: &'static str,
+    name: String,
+    version: String,
+}
+
+/// Files larger than this are not scanned by the built-in rules.
+const MAX_SCANNED_SIZE: u64 = 1024 * 1024;
+
+impl SignatureItem {
+    fn passed(&self) -> bool {
+        self.unsigned + self.unverified + self.bad + self.unsigned_tags + self.unverified_tags == 0
+    }
+}
+
+impl Rer {
+    pub(super) async fn audit(&self, commands: &AuditCommands) -> anyhow::Result<()> {
+        match commands {
+            AuditCommands::Signatures {
+                commits,
+                tags,
+                json,
+                filter,
+            } => {
+                let git = self.git();
+                let mut list = vec![];
+                for repo in self.scan_repos(filter).await? {
+                    if repo.ty != "git" {
+                        continue;
+                    }
+                    let dir = self.dir_of(&repo)?;
+                    if git.head(&dir).await.is_none() {
+                        continue;
+                    }
+                    let mut item = self.audit_signatures(&git, &dir, *commits, *tags).await?;
+                    item.path = repo.path;
+                    item.hostname = repo.hostname;
+                    list.push(item);
+                }
+                let failed = list.iter().filter(|item| !item.passed()).count();
+                if *json {
+                    println!("{}", serde_json::to_string(&list)?);
+                } else {
+                    println!("{}", self.render_table(tabled::Table::new(&list)));
+                }
+                if failed > 0 {
+                    return Err(anyhow!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-audit-unsigned",
+                            Some(vec![("count".to_string(), failed.to_string())])
+                        )
+                    ));
+                }
+                if !*json {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n
+                                .format_msg_or_log(&self.lang_id, "info-audit-signed", None)
+                        )
+                    );
+                }
+                Ok(())
+            }
+            AuditCommands::Secrets {
+                builtin,
+                json,
+                filter,
+            } => {
+                let gitleaks = !*builtin
+                    && tokio::process::Command::new("gitleaks")
+                        .arg("version")
+                        .output()
+                        .await
+                        .is_ok_and(|output| output.status.success());
+                let mut list = vec![];
+                for repo in self.scan_repos(filter).await? {
+                    let dir = self.dir_of(&repo)?;
+                    let files = self.worktree_files(&repo.ty, &dir).await?;
+                    let found = if gitleaks {
+                        self.gitleaks(&dir, &files).await?
+                    } else {
+                        self.scan_secrets(&dir, files).await?
+                    };
+                    list.extend(found.into_iter().map(|(file, line, rule)| SecretItem {
+                        path: repo.path.to_owned(),
+                        hostname: repo.hostname.to_owned(),
+                        file,
+                        line,
+                        rule,
+                    }));
+                }
+                if *json {
+                    println!("{}", serde_json::to_string(&list)?);
+                } else if !list.is_empty() {
+                    println!("{}", self.render_table(tabled::Table::new(&list)));
+                }
+                if !list.is_empty() {
+                    return Err(anyhow!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-audit-secrets",
+                            Some(vec![("count".to_string(), list.len().to_string())])
+                        )
+                    ));
+                }
+                if !*json {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-audit-no-secrets",
+                                None
+                            )
+                        )
+                    );
+                }
+                Ok(())
+            }
+            AuditCommands::Deps {
+                contains,
+                json,
+                filter,
+            } => {
+                let mut uses = vec![];
+                let mut failed = 0;
+                for repo in self.scan_repos(filter).await? {
+                    let dir = self.dir_of(&repo)?;
+                    match self.locked_deps(&repo.ty, &dir).await {
+                        Ok(locked) => {
+                            uses.extend(locked.into_iter().map(|(file, dep)| DependencyUse {
+                                path: repo.path.to_owned(),
+                                hostname: repo.hostname.to_owned(),
+                                file,
+                                ecosystem: dep.ecosystem,
+                                name: dep.name,
+                                version: dep.version,
+                            }))
+                        }
+                        Err(err) => {
+                            failed += 1;
+                            eprintln!(
+                                "{}",
+                                self.painter.paint(
+                                    Paint::Error,
+                                    self.i18n.format_msg_or_log(
+                                        &self.lang_id,
+                                        "error-audit-deps-failed",
+                                        Some(vec![
+                                            ("repo".to_string(), repo.path.to_owned()),
+                                            ("error".to_string(), err.to_string()),
+                                        ])
+                                    )
+                                )
+                            );
+                        }
+                    }
+                }
+                if let Some(query) = contains {
+                    uses.retain(|dep| deps::matches(&dep.name, query));
+                    if *json {
+                        println!("{}", serde_json::to_string(&uses)?);
+                    } else if uses.is_empty() {
+                        println!(
+                            "{}",
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-audit-deps-unused",
+                                Some(vec![("name".to_string(), query.to_owned())])
+                            )
+                        );
+                    } else {
+                        println!("{}", self.render_table(tabled::Table::new(&uses)));
+                    }
+                } else {
+                    let mut inventory = BTreeMap::<_, DependencyInventory>::new();
+                    for dep in uses {
+                        let entry = inventory
+                            .entry((dep.ecosystem, dep.name.to_owned()))
+                            .or_insert_with(|| DependencyInventory {
+                                ecosystem: dep.ecosystem,
+                                name: dep.name,
+                                versions: BTreeSet::new(),
+                                repos: BTreeSet::new(),
+                            });
+                        entry.versions.insert(dep.version);
+                        entry.repos.insert(format!("{}/{}", dep.hostname, dep.path));
+                    }
+                    let mut inventory = inventory.into_values().collect::<Vec<_>>();
+                    // the most used first, as those matter most in an incident
+                    inventory.sort_by_key(|dep| std::cmp::Reverse(dep.repos.len()));
+                    if *json {
+                        println!("{}", serde_json::to_string(&inventory)?);
+                    } else {
+                        println!(
+                            "{}",
+                            self.render_table(tabled::Table::new(inventory.into_iter().map(
+                                |dep| DependencyItem {
+                                    ecosystem: dep.ecosystem,
+                                    name: dep.name,
+                                    versions:
+                                        dep.versions.into_iter().collect::<Vec<_>>().join(", "),
+                                    repos: dep.repos.len(),
+                                }
+                            )))
+                        );
+                    }
+                }
+                if failed > 0 {
+                    return Err(anyhow!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-some-repos-failed",
+                            Some(vec![("count".to_string(), failed.to_string())])
+                        )
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The dependencies locked by the lockfiles anywhere in the working tree
+    /// of the repository in `dir`, with the lockfile locking them.
+    async fn locked_deps(
+        &self,
+        ty: &str,
+        dir: &Path,
+    ) -> anyhow::Result<Vec<(String, deps::Dependency)>> {
+        let mut locked = vec![];
+        for file in self.worktree_files(ty, dir).await? {
+            let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !deps::LOCKFILES.contains(&name) {
+                continue;
+            }
+            // deleted files are still listed until the deletion is staged
+            let Ok(text) = tokio::fs::read_to_string(dir.join(&file)).await else {
+                continue;
+            };
+            let file = file.to_string_lossy().to_string();
+            let deps = deps::parse(name, &text).map_err(|err| anyhow!("{}: {}", file, err))?;
+            locked.extend(deps.into_iter().map(|dep| (file.to_owned(), dep)));
+        }
+        Ok(locked)
+    }
+
+    /// The files, lines and rules of what gitleaks finds in `files` of the
+    /// working tree in `dir`. gitleaks scans the whole tree, what it finds in
+    /// other files, like those git ignores, is left out.
+    async fn gitleaks(
+        &self,
+        dir: &Path,
+        files: &[PathBuf],
+    ) -> anyhow::Result<Vec<(String, usize, String)>> {
+        let report = tempfile::NamedTempFile::new()?;
+        let output = tokio::process::Command::new("gitleaks")
+            .args(["detect", "--no-git", "--no-banner", "--exit-code", "0"])
+            .args(["--log-level", "error", "--report-format", "json"])
+            .arg("--source")
+            .arg(dir)
+            .arg("--report-path")
+            .arg(report.path())
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "gitleaks {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let findings: Vec<GitleaksFinding> =
+            serde_json::from_str(&tokio::fs::read_to_string(report.path()).await?)?;
+        let files = files.iter().collect::<HashSet<_>>();
+        Ok(findings
+            .into_iter()
+            .filter_map(|finding| {
+                let file = PathBuf::from(&finding.file);
+                let file = file.strip_prefix(dir).unwrap_or(&file);
+                files.contains(&file.to_path_buf()).then(|| {
+                    (
+                        file.to_string_lossy().to_string(),
+                        finding.start_line,
+                        finding.rule_id,
+                    )
+                })
+            })
+            .collect())
+    }
+
+    /// The files of the working tree of the repository of type `ty` in `dir`,
+    /// without those git ignores.
+    async fn worktree_files(&self, ty: &str, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        Ok(match ty {
+            "git" => self
+                .git()
+                .output(
+                    dir,
+                    &[
+                        "ls-files",
+                        "-z",
+                        "--cached",
+                        "--others",
+                        "--exclude-standard",
+                    ],
+                )
+                .await?
+                .split('\0')
+                .filter(|file| !file.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            _ => fs::files(dir),
+        })
+    }
+
+    /// The files, lines and rules of what the built-in rules find in the
+    /// text files of `files` of the repository in `dir`.
+    async fn scan_secrets(
+        &self,
+        dir: &Path,
+        files: Vec<PathBuf>,
+    ) -> anyhow::Result<Vec<(String, usize, String)>> {
+        let mut found = vec![];
+        for file in files {
+            let path = dir.join(&file);
+            // deleted files are still listed until the deletion is staged
+            let Ok(meta) = tokio::fs::metadata(&path).await else {
+                continue;
+            };
+            if !meta.is_file() || meta.len() > MAX_SCANNED_SIZE {
+                continue;
+            }
+            let Ok(text) = tokio::fs::read_to_string(&path).await else {
+                continue;
+            };
+            for (line, rule) in secrets::scan(&text) {
+                found.push((file.to_string_lossy().to_string(), line, rule.to_string()));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Checks the signatures of the last `commits` commits and `tags` tags of
+    /// the default branch of the repository in `dir`, the one of origin if
+    /// it is known and the checked out one otherwise.
+    async fn audit_signatures(
+        &self,
+        git: &Git,
+        dir: &Path,
+        commits: usize,
+        tags: usize,
+    ) -> anyhow::Result<SignatureItem> {
+        let remote_branch = match git.default_branch(dir, "origin").await {
+            Ok(branch) => git
+                .output(
+                    dir,
+                    &[
+                        "rev-parse",
+                        "--verify",
+                        "--quiet",
+                        &format!("refs/remotes/origin/{}", branch),
+                    ],
+                )
+                .await
+                .ok()
+                .map(|_| branch),
+            Err(_) => None,
+        };
+        let (branch, rev) = match remote_branch {
+            Some(branch) => (branch.to_owned(), format!("origin/{}", branch)),
+            None => (
+                git.current_branch(dir).await.unwrap_or_default(),
+                "HEAD".to_string(),
+            ),
+        };
+        let mut item = SignatureItem {
+            branch,
+            ..Default::default()
+        };
+        let count = format!("-n{}", commits);
+        for status in git
+            .output(dir, &["log", "--format=%G?", &count, &rev])
+            .await?
+            .lines()
+        {
+            item.commits += 1;
+            // see the %G? placeholder of git log
+            match status.trim() {
+                "G" => {}
+                "N" => item.unsigned += 1,
+                "B" => item.bad += 1,
+                _ => item.unverified += 1,
+            }
+        }
+        let count = format!("--count={}", tags);
+        let merged = format!("--merged={}", rev);
+        for tag in git
+            .output(
+                dir,
+                &[
+                    "for-each-ref",
+                    "--sort=-creatordate",
+                    &count,
+                    &merged,
+                    "--format=%(refname)",
+                    "refs/tags",
+                ],
+            )
+            .await?
+            .lines()
+        {
+            item.tags += 1;
+            if git.output(dir, &["verify-tag", tag]).await.is_ok() {
+                continue;
+            }
+            // lightweight tags are the commits themselves, whose signature is
+            // not one of the tag
+            let annotated = git
+                .output(dir, &["cat-file", "-t", tag])
+                .await
+                .is_ok_and(|ty| ty.trim() == "tag");
+            let object = if annotated {
+                git.output(dir, &["cat-file", "-p", tag])
+                    .await
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+            if object.contains("-----BEGIN ") && object.contains(" SIGNATURE-----") {
+                item.unverified_tags += 1;
+            } else {
+                item.unsigned_tags += 1;
+            }
+        }
+        Ok(item)
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use tabled::Tabled;
+
+use crate::{
+    cli::{BranchesCommands, RepoFilter},
+    git::Git,
+    style::Paint,
+};
+
+use super::Rer;
+
+#[derive(Tabled)]
+struct BranchTableItem {
+    path: String,
+    hostname: String,
+    branch: String,
+    branches: usize,
+    prunable: usize,
+}
+
+/// A local branch and whether it can be pruned.
+struct Branch {
+    name: String,
+    merged: bool,
+    gone: bool,
+}
+
+impl Branch {
+    fn prunable(&self) -> bool {
+        self.merged || self.gone
+    }
+}
+
+impl Rer {
+    pub(super) async fn branches(
+        &self,
+        commands: Option<&BranchesCommands>,
+        filter: &RepoFilter,
+    ) -> anyhow::Result<()> {
+        match commands {
+            None => self.list_branches(filter).await,
+            Some(BranchesCommands::Prune { filter, apply }) => {
+                self.prune_branches(filter, *apply).await
+            }
+        }
+    }
+
+    async fn list_branches(&self, filter: &RepoFilter) -> anyhow::Result<()> {
+        let git = self.git();
+        let mut list = vec![];
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            let branches = local_branches(&git, &dir).await?;
+            list.push(BranchTableItem {
+                path: repo.path.to_owned(),
+                hostname: repo.hostname.to_owned(),
+                branch: git.current_branch(&dir).await.unwrap_or_default(),
+                branches: branches.len(),
+                prunable: branches.iter().filter(|branch| branch.prunable()).count(),
+            });
+        }
+        println!("{}", self.render_table(tabled::Table::new(list)));
+        Ok(())
+    }
+
+    async fn prune_branches(&self, filter: &RepoFilter, apply: bool) -> anyhow::Result<()> {
+        let git = self.git();
+        let mut failed = 0;
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            let branches = match local_branches(&git, &dir).await {
+                Ok(branches) => branches,
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-branch-prune-failed",
+                                Some(vec![
+                                    ("repo".to_string(), repo.path.to_owned()),
+                                    ("error".to_string(), err.to_string()),
+                                ])
+                            )
+                        )
+                    );
+                    continue;
+                }
+            };
+            for branch in branches.iter().filter(|branch| branch.prunable()) {
+                let params = Some(vec![
+                    ("repo".to_string(), repo.path.to_owned()),
+                    ("branch".to_string(), branch.name.to_owned()),
+                ]);
+                if !apply {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-branch-would-prune",
+                                params
+                            )
+                        )
+                    );
+                    continue;
+                }
+                let flag = if branch.merged { "-d" } else { "-D" };
+                match git.output(&dir, &["branch", flag, &branch.name]).await {
+                    Ok(_) => println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-branch-pruned",
+                                params
+                            )
+                        )
+                    ),
+                    Err(err) => {
+                        failed += 1;
+                        eprintln!(
+                            "{}",
+                            self.painter.paint(
+                                Paint::Error,
+                                self.i18n.format_msg_or_log(
+                                    &self.lang_id,
+                                    "error-branch-prune-failed",
+                                    Some(vec![
+                                        ("repo".to_string(), repo.path.to_owned()),
+                                        ("error".to_string(), err.to_string()),
+                                    ])
+                                )
+                            )
+                        );
+                    }
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The local branches of the repository at `dir`. Branches merged into the
+/// default branch on origin, `origin/<default>`, or the current branch without
+/// origin, and
+/// branches whose upstream is gone are prunable. The current and the default
+/// branch never are.
+async fn local_branches(git: &Git, dir: &Path) -> anyhow::Result<Vec<Branch>> {
+    let current = git.current_branch(dir).await.unwrap_or_default();
+    // branches merged upstream count before the default branch is pulled
+    let (base, base_ref) = match git
+        .remotes(dir)
+        .await?
+        .iter()
+        .any(|remote| remote == "origin")
+    {
+        true => match git.default_branch(dir, "origin").await {
+            Ok(default) => (default.clone(), format!("origin/{}", default)),
+            Err(_) => (current.to_owned(), current.to_owned()),
+        },
+        false => (current.to_owned(), current.to_owned()),
+    };
+    let unborn = git
+        .output(dir, &["rev-parse", "--verify", "--quiet", &base_ref])
+        .await
+        .is_err();
+    let merged = if base_ref.is_empty() || unborn {
+        vec![]
+    } else {
+        git.output(
+            dir,
+            &["branch", "--format=%(refname:short)", "--merged", &base_ref],
+        )
+        .await?
+        .lines()
+        .map(str::to_string)
+        .collect()
+    };
+    Ok(git
+        .output(
+            dir,
+            &[
+                "for-each-ref",
+                "--format=%(refname:short)\t%(upstream:track)",
+                "refs/heads",
+            ],
+        )
+        .await?
+        .lines()
+        .map(|line| {
+            let (name, track) = line.split_once('\t').unwrap_or((line, ""));
+            let protected = name == current || name == base;
+            Branch {
+                name: name.to_string(),
+                merged: !protected && merged.iter().any(|branch| branch == name),
+                gone: !protected && track == "[gone]",
+            }
+        })
+        .collect())
+}
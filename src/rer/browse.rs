@@ -0,0 +1,79 @@
+use anyhow::anyhow;
+
+use crate::{
+    clipboard,
+    forge::{Forge, WebPage},
+    git::GitUrl,
+    style::Paint,
+};
+
+use super::{open::uri_handler, Rer};
+
+impl Rer {
+    /// Opens a page of `target` on its forge, the forge of the host config or
+    /// of a well known hostname, or copies its url with `copy`.
+    pub(super) async fn browse(
+        &self,
+        target: &str,
+        commit: Option<&str>,
+        pr: Option<u64>,
+        file: Option<&str>,
+        line: Option<u32>,
+        copy: bool,
+    ) -> anyhow::Result<()> {
+        let dir = self.find_repo(target).await?;
+        let git = self.git();
+        let url = GitUrl::parse(git.remote_url(&dir, "origin").await?)?;
+        let forge = match self.forge_of_host(url.host()) {
+            Some(name) => Forge::from_name(name),
+            None => Forge::detect(url.host()),
+        }
+        .ok_or_else(|| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-no-forge-of-host",
+                    Some(vec![("host".to_string(), url.host().to_string())])
+                )
+            )
+        })?;
+        let head;
+        let page = match (commit, pr, file) {
+            (Some(commit), _, _) => WebPage::Commit(commit),
+            (_, Some(pr), _) => WebPage::PullRequest(pr),
+            (_, _, Some(path)) => {
+                head = git.output(&dir, &["rev-parse", "HEAD"]).await?;
+                WebPage::File {
+                    commit: head.trim(),
+                    path,
+                    line,
+                }
+            }
+            _ => WebPage::Repo,
+        };
+        let web_url = forge.web_url(url.host(), &url.project_path(), &page);
+        if copy {
+            clipboard::copy(&web_url).await?;
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-copied",
+                        Some(vec![("text".to_string(), web_url)])
+                    )
+                )
+            );
+            return Ok(());
+        }
+        let (cmd, args) = uri_handler(&web_url, "");
+        tokio::process::Command::new(cmd)
+            .args(args)
+            .spawn()?
+            .wait()
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+
+use crate::{cli::CacheCommands, fs::PartialDir, git::GitUrl, style::Paint};
+
+use super::Rer;
+
+impl Rer {
+    pub(super) async fn cache(&self, commands: &CacheCommands) -> anyhow::Result<()> {
+        let git = self.git();
+        match commands {
+            CacheCommands::Warm { url } => {
+                let (url, target) = self.resolve_clone_target(url)?;
+                let mirror = self.mirror_of(&url)?;
+                if mirror.exists() {
+                    git.output(&mirror, &["remote", "update", "--prune"])
+                        .await?;
+                } else {
+                    let partial = PartialDir::new(&mirror);
+                    let status = git
+                        .clone(&["--mirror"], &target, mirror.to_string_lossy())
+                        .await?;
+                    if !status.success() {
+                        return Err(anyhow!("git clone {}", status));
+                    }
+                    partial.keep();
+                }
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-cache-warmed",
+                            Some(vec![(
+                                "dir".to_string(),
+                                mirror.to_string_lossy().to_string()
+                            )])
+                        )
+                    )
+                );
+                Ok(())
+            }
+            CacheCommands::Serve { port } => {
+                let cache_dir = self.cache_dir()?;
+                tokio::fs::create_dir_all(&cache_dir).await?;
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-cache-serving",
+                            Some(vec![
+                                ("dir".to_string(), cache_dir.to_string_lossy().to_string()),
+                                ("port".to_string(), port.to_string()),
+                            ])
+                        )
+                    )
+                );
+                let status = git.daemon(&cache_dir, *port).await?;
+                if !status.success() {
+                    return Err(anyhow!("git daemon {}", status));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn cache_dir(&self) -> anyhow::Result<PathBuf> {
+        Ok(self.data_dir()?.join("cache"))
+    }
+
+    /// The bare mirror of `url` in the cache, `<host>/<owner>/<name>.git`.
+    fn mirror_of(&self, url: &GitUrl) -> anyhow::Result<PathBuf> {
+        Ok(self
+            .cache_dir()?
+            .join(url.host())
+            .join(format!("{}.git", url.project_path())))
+    }
+
+    /// Options making a clone of `url` borrow objects from its cached mirror.
+    /// The clone is dissociated so pruning the mirror can not break it.
+    pub(super) fn reference_options(&self, url: &GitUrl) -> anyhow::Result<Vec<String>> {
+        let mirror = self.mirror_of(url)?;
+        Ok(if mirror.is_dir() {
+            vec![
+                "--reference-if-able".to_string(),
+                mirror.to_string_lossy().to_string(),
+                "--dissociate".to_string(),
+            ]
+        } else {
+            vec![]
+        })
+    }
+}
@@ -0,0 +1,151 @@
+use std::{path::Path, time::Instant};
+
+use anyhow::anyhow;
+use log::debug;
+use url::Url;
+
+use crate::{
+    fs::PartialDir,
+    git::{Git, GitUrl},
+    layout,
+    outcome::Outcome,
+    vendor,
+};
+
+use super::{hooks::Hook, Rer};
+
+impl Rer {
+    /// Clones `target`, printing an [`Outcome`] instead of the output of git
+    /// with `json`.
+    pub(super) async fn clone(
+        &self,
+        ty: &str,
+        origin_url: Option<&str>,
+        target: &str,
+        json: bool,
+    ) -> anyhow::Result<()> {
+        let start = Instant::now();
+        let git = self.git();
+        let outcome = match ty {
+            "git" => {
+                if target.ends_with(".bundle") {
+                    // bundles carry no origin, so the layout comes from the given url
+                    let origin_url = origin_url.ok_or_else(|| {
+                        anyhow!(
+                            "{}",
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-bundle-needs-origin-url",
+                                None
+                            )
+                        )
+                    })?;
+                    let path = self.path_of_git_url(&GitUrl::parse(origin_url)?)?;
+                    let partial = PartialDir::new(&path);
+                    let (status, stderr) = self.git_clone(&git, None, target, &path, json).await?;
+                    if status.success() {
+                        partial.keep();
+                        git.output(&path, &["remote", "set-url", "origin", origin_url])
+                            .await?;
+                    }
+                    Outcome::new(&path, start).with_exit(status, &stderr)
+                } else {
+                    let (url, target) = self.resolve_clone_target(target)?;
+                    let path = self.path_of_git_url(&url)?;
+                    let partial = PartialDir::new(&path);
+                    let (status, stderr) = self
+                        .git_clone(&git, Some(&url), &target, &path, json)
+                        .await?;
+                    if status.success() {
+                        partial.keep();
+                        if let Err(err) = self.fetch_meta(&url, &path).await {
+                            debug!("fetching metadata failed: {:?}", err);
+                        }
+                    }
+                    Outcome::new(&path, start).with_exit(status, &stderr)
+                }
+            }
+            "vendor" => {
+                let (host, path) = vendor::vendor_path(&Url::parse(target)?)?;
+                let (owner, name) = layout::split_path(&path);
+                let path = self.path_of_repo(ty, host, owner, name)?;
+                match vendor::fetch(target, &path).await {
+                    Ok(_) => Outcome::new(&path, start),
+                    Err(err) if json => Outcome::new(&path, start).with_error(&err),
+                    Err(err) => return Err(err),
+                }
+            }
+            ty => match self.clone_with_plugin(ty, target, None).await {
+                Ok(path) => Outcome::new(&path, start),
+                Err(err) if json => Outcome::new(target, start).with_error(&err),
+                Err(err) => return Err(err),
+            },
+        };
+        if outcome.success {
+            self.record_cloned_by(Path::new(&outcome.path)).await;
+            self.run_hook(Hook::PostClone, Path::new(&outcome.path))
+                .await;
+        }
+        if json {
+            println!("{}", serde_json::to_string(&outcome)?);
+        }
+        if !outcome.success {
+            let error = match outcome.exit_code {
+                Some(code) => format!("exit status: {}", code),
+                None => outcome.stderr_tail,
+            };
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-repo-clone-failed",
+                    Some(vec![
+                        ("repo".to_string(), target.to_string()),
+                        ("error".to_string(), error),
+                    ])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses `target`, resolving shorthands like `github.com/owner/repo` to a
+    /// url with the protocol of their host.
+    pub(super) fn resolve_clone_target(&self, target: &str) -> anyhow::Result<(GitUrl, String)> {
+        Ok(match GitUrl::parse_shorthand(target) {
+            Some(url) => {
+                let resolved = url
+                    .to_protocol(self.protocol_of_host(url.host()))
+                    .unwrap_or_else(|| target.to_owned());
+                (url, resolved)
+            }
+            None => (GitUrl::parse(target)?, target.to_owned()),
+        })
+    }
+
+    /// Clones with the output of git shown, or captured with `capture`,
+    /// borrowing objects from the cached mirror of `url` if there is one.
+    async fn git_clone(
+        &self,
+        git: &Git,
+        url: Option<&GitUrl>,
+        target: &str,
+        path: &std::path::Path,
+        capture: bool,
+    ) -> anyhow::Result<(std::process::ExitStatus, String)> {
+        let path = path.to_string_lossy();
+        let options = match url {
+            Some(url) => self.reference_options(url)?,
+            None => vec![],
+        };
+        let options = options.iter().map(String::as_str).collect::<Vec<_>>();
+        if capture {
+            let mut args = vec!["clone"];
+            args.extend(&options);
+            args.extend(["--", target, &path]);
+            git.run_captured(&args).await
+        } else {
+            Ok((git.clone(&options, target, path).await?, String::new()))
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::{cli::RepoFilter, git::Git, style::Paint};
+
+use super::Rer;
+
+impl Rer {
+    /// Commits with `message` in every matching dirty git repository, staging
+    /// `add` first, skipping repositories with nothing staged.
+    pub(super) async fn commit_all(
+        &self,
+        filter: &RepoFilter,
+        message: &str,
+        add: &[String],
+    ) -> anyhow::Result<()> {
+        let git = self.git();
+        let mut committed = 0;
+        let mut failed = 0;
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            if !git.status(&dir).await.is_ok_and(|status| status.dirty) {
+                continue;
+            }
+            match self.commit_repo(&git, &dir, message, add).await {
+                Ok(Some(commit)) => {
+                    committed += 1;
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-committed",
+                                Some(vec![
+                                    ("repo".to_string(), repo.path.to_owned()),
+                                    ("commit".to_string(), commit),
+                                ])
+                            )
+                        )
+                    );
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-commit-failed",
+                                Some(vec![
+                                    ("repo".to_string(), repo.path.to_owned()),
+                                    ("error".to_string(), err.to_string()),
+                                ])
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        println!(
+            "{}",
+            self.i18n.format_msg_or_log(
+                &self.lang_id,
+                "info-commits-created",
+                Some(vec![("count".to_string(), committed.to_string())])
+            )
+        );
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// The short hash of the commit made in `dir`, `None` if nothing was
+    /// staged.
+    async fn commit_repo(
+        &self,
+        git: &Git,
+        dir: &Path,
+        message: &str,
+        add: &[String],
+    ) -> anyhow::Result<Option<String>> {
+        if !add.is_empty() {
+            // git add fails for pathspecs matching nothing, as in repositories
+            // the change was not made in
+            let mut args = vec!["status", "--porcelain", "--"];
+            args.extend(add.iter().map(String::as_str));
+            if !git.output(dir, &args).await?.trim().is_empty() {
+                args.splice(..2, ["add", "--all"]);
+                git.output(dir, &args).await?;
+            }
+        }
+        // exits successfully only without staged changes
+        if git
+            .output(dir, &["diff", "--cached", "--quiet"])
+            .await
+            .is_ok()
+        {
+            return Ok(None);
+        }
+        git.output(dir, &["commit", "--quiet", "-m", message])
+            .await?;
+        Ok(Some(
+            git.output(dir, &["rev-parse", "--short", "HEAD"])
+                .await?
+                .trim()
+                .to_string(),
+        ))
+    }
+}
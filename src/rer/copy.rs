@@ -0,0 +1,42 @@
+use anyhow::anyhow;
+
+use crate::{clipboard, meta::RepoMeta, style::Paint};
+
+use super::Rer;
+
+impl Rer {
+    /// Copies the absolute directory of `target`, or with `url` the url it
+    /// was cloned from, to the clipboard.
+    pub(super) async fn copy(&self, target: &str, url: bool) -> anyhow::Result<()> {
+        let dir = self.find_repo(target).await?;
+        let text = if !url {
+            dir.canonicalize()?.to_string_lossy().to_string()
+        } else if dir.join(".git").exists() {
+            self.git().remote_url(&dir, "origin").await?
+        } else {
+            RepoMeta::load(&dir).await?.url.ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-no-url",
+                        Some(vec![("repo".to_string(), target.to_string())])
+                    )
+                )
+            })?
+        };
+        clipboard::copy(&text).await?;
+        println!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "info-copied",
+                    Some(vec![("text".to_string(), text)])
+                )
+            )
+        );
+        Ok(())
+    }
+}
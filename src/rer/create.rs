@@ -0,0 +1,375 @@
+use std::{collections::BTreeMap, io::IsTerminal, path::Path, process::Stdio, time::Instant};
+
+use anyhow::anyhow;
+use dialoguer::Input;
+use log::debug;
+
+use crate::{
+    forge::Forge,
+    fs,
+    git::GitUrl,
+    layout,
+    outcome::Outcome,
+    scaffold::{self, Scaffold, TemplateSpec, Tool},
+    style::Paint,
+};
+
+use super::{hooks::Hook, target, Rer};
+
+impl Rer {
+    /// `target` with the user of the access token for `hostname` as owner if
+    /// it has none, or `target` itself without a token.
+    pub(super) async fn owned_target(&self, hostname: &str, target: &str) -> String {
+        if target.contains('/') {
+            return target.to_string();
+        }
+        let login = match self.forge_with_token(hostname) {
+            Ok((forge, token)) => match self.api() {
+                Ok(api) => forge.login(api, hostname, &token).await,
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+        match login {
+            Ok(login) => format!("{}/{}", login, target),
+            Err(err) => {
+                debug!("no owner from the forge of {}: {:?}", hostname, err);
+                target.to_string()
+            }
+        }
+    }
+
+    /// Turns the directory `from` into the repository `target` of `hostname`,
+    /// with `publish` also creating it on the forge and pushing it.
+    pub(super) async fn create_from(
+        &self,
+        hostname: Option<&str>,
+        target: &str,
+        from: &Path,
+        publish: bool,
+        json: bool,
+    ) -> anyhow::Result<()> {
+        let start = Instant::now();
+        let (target_host, target) = match hostname {
+            Some(_) => (None, target),
+            None => target::split_host(target),
+        };
+        let hostname = hostname
+            .or(target_host)
+            .or(self.config.default_host.as_deref())
+            .unwrap_or("localhost");
+        let target = self.owned_target(hostname, target).await;
+        let (owner, name) = layout::split_path(&target);
+        let path = self.path_of_repo("git", hostname, owner, name)?;
+        match self
+            .adopt_dir(hostname, &target, from, &path, publish)
+            .await
+        {
+            Ok(published_to) if json => {
+                let outcome = Outcome {
+                    published_to,
+                    ..Outcome::new(&path, start)
+                };
+                println!("{}", serde_json::to_string(&outcome)?);
+            }
+            Ok(published) => {
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-repo-moved",
+                            Some(vec![(
+                                "dir".to_string(),
+                                path.to_string_lossy().to_string()
+                            )])
+                        )
+                    )
+                );
+                if let Some(url) = published {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-repo-published",
+                                Some(vec![("url".to_string(), url)])
+                            )
+                        )
+                    );
+                }
+            }
+            Err(err) => {
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&Outcome::new(&path, start).with_error(&err))?
+                    );
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes git in `from` if it is no repository yet, commits everything
+    /// in it if it has no commits, and moves it to `path`. Returns the url it was
+    /// published to with `publish`.
+    async fn adopt_dir(
+        &self,
+        hostname: &str,
+        target: &str,
+        from: &Path,
+        path: &Path,
+        publish: bool,
+    ) -> anyhow::Result<Option<String>> {
+        if !from.is_dir() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-not-a-dir",
+                    Some(vec![(
+                        "dir".to_string(),
+                        from.to_string_lossy().to_string()
+                    )])
+                )
+            ));
+        }
+        // a missing forge or token fails before anything is changed
+        let forge = match publish {
+            true => Some(self.forge_with_token(hostname)?),
+            false => None,
+        };
+        let git = self.git();
+        if !from.join(".git").exists() {
+            git.output(from, &["init"]).await?;
+        }
+        // a failed first commit leaves an empty repository to retry with
+        if git
+            .output(from, &["rev-parse", "--verify", "--quiet", "HEAD"])
+            .await
+            .is_err()
+        {
+            git.output(from, &["add", "--all"]).await?;
+            git.output(
+                from,
+                &["commit", "--allow-empty", "--message", "Initial commit"],
+            )
+            .await?;
+        }
+        fs::move_dir(from, path).await?;
+        self.record_cloned_by(path).await;
+        self.run_hook(Hook::PostCreate, path).await;
+        let Some((forge, token)) = forge else {
+            return Ok(None);
+        };
+        let url = self.publish(forge, &token, hostname, target, path).await?;
+        Ok(Some(url))
+    }
+
+    /// Creates `target` on `forge` and pushes the repository in `dir` to it as
+    /// origin, returning its url.
+    async fn publish(
+        &self,
+        forge: Forge,
+        token: &str,
+        hostname: &str,
+        target: &str,
+        dir: &Path,
+    ) -> anyhow::Result<String> {
+        let (owner, name) = layout::split_path(target);
+        forge
+            .create_repo(self.api()?, hostname, owner, name, token)
+            .await?;
+        let https = format!("https://{}/{}.git", hostname, target);
+        let url = GitUrl::parse(&https)?
+            .to_protocol(self.protocol_of_host(hostname))
+            .unwrap_or(https);
+        let git = self.git();
+        if git
+            .remotes(dir)
+            .await?
+            .iter()
+            .any(|remote| remote == "origin")
+        {
+            git.output(dir, &["remote", "set-url", "origin", &url])
+                .await?;
+        } else {
+            git.output(dir, &["remote", "add", "origin", &url]).await?;
+        }
+        git.output(dir, &["push", "--set-upstream", "origin", "HEAD"])
+            .await?;
+        Ok(url)
+    }
+
+    /// The project template `template` with the values of its variables for
+    /// the repository `owner/name` of `hostname`, taken from `vars` like
+    /// `key=value`, asked for in a terminal, or their defaults. Templates of
+    /// other tools get `vars` and ask for the rest themselves.
+    pub(super) async fn prepare_template(
+        &self,
+        template: &str,
+        vars: &[String],
+        hostname: &str,
+        target: &str,
+    ) -> anyhow::Result<Scaffold> {
+        let error = |key: &str, params: Vec<(&str, &str)>| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    key,
+                    Some(
+                        params
+                            .into_iter()
+                            .map(|(key, value)| (key.to_string(), value.to_string()))
+                            .collect()
+                    )
+                )
+            )
+        };
+        let mut given = BTreeMap::new();
+        for var in vars {
+            let (key, value) = var
+                .split_once('=')
+                .ok_or_else(|| error("error-invalid-var", vec![("var", var)]))?;
+            given.insert(key.to_string(), value.to_string());
+        }
+        if let Some((tool, template)) = Tool::of(template) {
+            let source = self.external_template_source(template).await?;
+            return Ok(Scaffold::External(tool, source, given));
+        }
+        let dir = self.template_dir(template).await?;
+        let spec = TemplateSpec::load(&dir).await?;
+        let (owner, name) = layout::split_path(target);
+        let mut values = BTreeMap::from([
+            ("hostname".to_string(), hostname.to_string()),
+            ("owner".to_string(), owner.to_string()),
+            ("name".to_string(), name.to_string()),
+        ]);
+        let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        for variable in &spec.variables {
+            let pattern = variable
+                .pattern
+                .as_deref()
+                .map(|pattern| {
+                    lazy_regex::Regex::new(&format!("^(?:{})$", pattern)).map_err(|err| {
+                        error(
+                            "error-invalid-var-pattern",
+                            vec![("var", &variable.name), ("error", &err.to_string())],
+                        )
+                    })
+                })
+                .transpose()?;
+            let default = variable
+                .default
+                .as_deref()
+                .map(|default| scaffold::render(default, &values));
+            let value = match (given.remove(&variable.name), default) {
+                (Some(value), _) => value,
+                (None, default) if interactive => {
+                    let mut input = Input::<String>::new()
+                        .with_prompt(variable.prompt.as_deref().unwrap_or(&variable.name));
+                    if let Some(default) = default {
+                        input = input.default(default);
+                    }
+                    if let Some(ref pattern) = pattern {
+                        let pattern = pattern.clone();
+                        let mismatch = self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-template-var-mismatch",
+                            Some(vec![(
+                                "pattern".to_string(),
+                                variable.pattern.to_owned().unwrap_or_default(),
+                            )]),
+                        );
+                        input = input.validate_with(move |value: &String| {
+                            match pattern.is_match(value) {
+                                true => Ok(()),
+                                false => Err(mismatch.clone()),
+                            }
+                        });
+                    }
+                    input.interact_text()?
+                }
+                (None, Some(default)) => default,
+                (None, None) => {
+                    return Err(error(
+                        "error-template-var-missing",
+                        vec![("var", &variable.name)],
+                    ))
+                }
+            };
+            if let Some(ref pattern) = pattern {
+                if !pattern.is_match(&value) {
+                    return Err(error(
+                        "error-template-var-invalid",
+                        vec![
+                            ("var", &variable.name),
+                            ("value", &value),
+                            ("pattern", variable.pattern.as_deref().unwrap_or_default()),
+                        ],
+                    ));
+                }
+            }
+            values.insert(variable.name.to_owned(), value);
+        }
+        // values of undeclared variables are still rendered
+        values.extend(given);
+        Ok(Scaffold::Template(dir, values))
+    }
+
+    /// Fills the new repository in `path` from `scaffold`, with the output
+    /// of other tools kept off stdout and their questions skipped with `json`.
+    pub(super) async fn fill_from_template(
+        &self,
+        scaffold: &Scaffold,
+        path: &Path,
+        json: bool,
+    ) -> anyhow::Result<()> {
+        let (tool, source, vars) = match scaffold {
+            Scaffold::Template(dir, values) => return scaffold::render_dir(dir, path, values),
+            Scaffold::External(tool, source, vars) => (tool, source, vars),
+        };
+        let interactive =
+            !json && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        // cookiecutter creates a directory named by the template in the output
+        let output = tempfile::tempdir_in(path.parent().unwrap_or(Path::new(".")))?;
+        let args = tool.args(source, vars, path, output.path(), interactive);
+        let mut command = tokio::process::Command::new(tool.exe());
+        command.args(&args).kill_on_drop(true);
+        if json {
+            command.stdout(Stdio::null());
+        }
+        let status = command.status().await.map_err(|err| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-template-tool-missing",
+                    Some(vec![
+                        ("tool".to_string(), tool.exe().to_string()),
+                        ("error".to_string(), err.to_string()),
+                    ])
+                )
+            )
+        })?;
+        if !status.success() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-template-tool-failed",
+                    Some(vec![
+                        ("tool".to_string(), tool.exe().to_string()),
+                        ("status".to_string(), status.to_string()),
+                    ])
+                )
+            ));
+        }
+        scaffold::move_generated(output.path(), path)
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::IsTerminal;
+
+use anyhow::anyhow;
+use dialoguer::MultiSelect;
+use tabled::Tabled;
+
+use crate::{git::GitUrl, style::Paint};
+
+use super::Rer;
+
+#[derive(Tabled)]
+struct DiscoveredItem {
+    repo: String,
+    cloned: String,
+    description: String,
+}
+
+impl Rer {
+    /// Lists the repositories on `host` starred by the owner of its token, or
+    /// watched with `watched`, marking the cloned ones, and clones the others
+    /// with `all` or those picked in a terminal.
+    pub(super) async fn discover(
+        &self,
+        host: &str,
+        watched: bool,
+        all: bool,
+    ) -> anyhow::Result<()> {
+        let (forge, token) = self.forge_with_token(host)?;
+        let mut missing = vec![];
+        let mut list = vec![];
+        for repo in forge.starred(self.api()?, host, &token, watched).await? {
+            let cloned = self
+                .path_of_git_url(&GitUrl::parse(&repo.clone_url)?)?
+                .exists();
+            if !cloned {
+                missing.push((repo.project.to_owned(), repo.clone_url));
+            }
+            list.push(DiscoveredItem {
+                repo: repo.project,
+                cloned: if cloned { "yes" } else { "" }.to_string(),
+                description: repo.description.unwrap_or_default(),
+            });
+        }
+        println!("{}", self.render_table(tabled::Table::new(list)));
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let picked = if all {
+            missing
+        } else if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+            let names = missing
+                .iter()
+                .map(|(project, _)| project.as_str())
+                .collect::<Vec<_>>();
+            let picked = MultiSelect::new()
+                .with_prompt(self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "prompt-discover-clone",
+                    None,
+                ))
+                .items(&names)
+                .interact()?;
+            picked
+                .into_iter()
+                .map(|index| missing[index].to_owned())
+                .collect()
+        } else {
+            println!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "info-discover-not-cloned",
+                    Some(vec![("count".to_string(), missing.len().to_string())])
+                )
+            );
+            return Ok(());
+        };
+        let mut failed = 0;
+        for (project, clone_url) in picked {
+            // cloned like `clone host/owner/name`, with the protocol of the host
+            let shorthand = format!("{}/{}", host, project);
+            let target = match GitUrl::parse_shorthand(&shorthand) {
+                Some(_) => shorthand,
+                None => clone_url,
+            };
+            if let Err(err) = self.clone("git", None, &target, false).await {
+                failed += 1;
+                eprintln!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Error,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-repo-clone-failed",
+                            Some(vec![
+                                ("repo".to_string(), target),
+                                ("error".to_string(), err.to_string()),
+                            ])
+                        )
+                    )
+                );
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+}
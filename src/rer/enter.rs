@@ -0,0 +1,61 @@
+use std::process::Stdio;
+
+use anyhow::anyhow;
+
+use crate::meta::RepoMeta;
+
+use super::Rer;
+
+impl Rer {
+    /// Runs the dev environment command of `target` in its directory,
+    /// falling back to the user's shell.
+    pub(super) async fn enter(&self, with: Option<&str>, target: &str) -> anyhow::Result<()> {
+        let repo = self.find_repo_item(target).await?;
+        let target_dir = self.dir_of(&repo)?;
+        let meta = RepoMeta::load(&target_dir).await?;
+        let command = with
+            .map(str::to_string)
+            .or(meta.enter)
+            .or_else(|| {
+                self.config
+                    .owners
+                    .as_ref()
+                    .and_then(|owners| owners.get(&repo.owner))
+                    .and_then(|owner| owner.enter.to_owned())
+            })
+            .or_else(|| self.config.enter.to_owned());
+        let mut child = match command {
+            Some(command) if cfg!(target_os = "windows") => {
+                let mut child = tokio::process::Command::new("cmd");
+                child.arg("/C").arg(command);
+                child
+            }
+            Some(command) => {
+                let mut child = tokio::process::Command::new("sh");
+                child.arg("-c").arg(command);
+                child
+            }
+            None => tokio::process::Command::new(
+                std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string()),
+            ),
+        };
+        let status = child
+            .current_dir(&target_dir)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .await?;
+        if !status.success() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-enter-failed",
+                    Some(vec![("status".to_string(), status.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+}
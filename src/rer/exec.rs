@@ -0,0 +1,272 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::SystemTime,
+};
+
+use anyhow::anyhow;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::Command,
+    task::JoinSet,
+};
+
+use crate::{
+    cli::{ExecOutput, RepoFilter},
+    style::Paint,
+};
+
+use super::Rer;
+
+struct ExecResult {
+    path: String,
+    log_file: Option<PathBuf>,
+    result: anyhow::Result<(std::process::ExitStatus, Vec<u8>, Vec<u8>)>,
+}
+
+impl Rer {
+    /// Runs `command` in each matching repository, `jobs` at a time, with the
+    /// output of the repositories handled as `output` says.
+    pub(super) async fn exec(
+        &self,
+        filter: &RepoFilter,
+        output: ExecOutput,
+        jobs: Option<usize>,
+        command: &[String],
+    ) -> anyhow::Result<()> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| anyhow!("empty command"))?;
+        let jobs = jobs
+            .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1)
+            .max(1);
+        let log_dir = match output {
+            ExecOutput::Files => {
+                let logs = self.data_dir()?.join("exec");
+                std::fs::create_dir_all(&logs)?;
+                // named by the time to sort, with a unique suffix for runs
+                // started in the same second
+                let started = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)?
+                    .as_secs();
+                Some(
+                    tempfile::Builder::new()
+                        .prefix(&format!("{}-", started))
+                        .tempdir_in(&logs)?
+                        .into_path(),
+                )
+            }
+            _ => None,
+        };
+
+        let mut running = JoinSet::new();
+        let mut results = vec![];
+        for repo in self.scan_repos(filter).await? {
+            if running.len() >= jobs {
+                if let Some(result) = running.join_next().await {
+                    results.push(self.report_exec(result?, output));
+                }
+            }
+            let mut child = Command::new(program);
+            child.args(args).current_dir(self.dir_of(&repo)?);
+            let log_file = log_dir.as_ref().map(|dir| {
+                dir.join(&repo.ty)
+                    .join(&repo.hostname)
+                    .join(format!("{}.log", repo.path))
+            });
+            running.spawn(run(child, repo.path, output, log_file));
+        }
+        while let Some(result) = running.join_next().await {
+            results.push(self.report_exec(result?, output));
+        }
+
+        if let Some(ref log_dir) = log_dir {
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-exec-logs",
+                        Some(vec![(
+                            "dir".to_string(),
+                            log_dir.to_string_lossy().to_string()
+                        )])
+                    )
+                )
+            );
+            for (path, log_file, error) in &results {
+                let mut params = vec![
+                    ("repo".to_string(), path.to_owned()),
+                    (
+                        "file".to_string(),
+                        log_file
+                            .as_deref()
+                            .map(Path::to_string_lossy)
+                            .unwrap_or_default()
+                            .to_string(),
+                    ),
+                ];
+                match error {
+                    Some(error) => {
+                        params.push(("error".to_string(), error.to_owned()));
+                        eprintln!(
+                            "{}",
+                            self.painter.paint(
+                                Paint::Error,
+                                self.i18n.format_msg_or_log(
+                                    &self.lang_id,
+                                    "error-exec-failed-log",
+                                    Some(params)
+                                )
+                            )
+                        );
+                    }
+                    None => println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-exec-log",
+                                Some(params)
+                            )
+                        )
+                    ),
+                }
+            }
+        }
+
+        let failed = results
+            .iter()
+            .filter(|(_, _, error)| error.is_some())
+            .count();
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Prints the output of a finished repository in grouped mode and its
+    /// failure, except in files mode where failures go into the final index.
+    fn report_exec(
+        &self,
+        exec: ExecResult,
+        output: ExecOutput,
+    ) -> (String, Option<PathBuf>, Option<String>) {
+        let error = match exec.result {
+            Ok((status, stdout, stderr)) => {
+                if let ExecOutput::Grouped = output {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-exec-group",
+                                Some(vec![("repo".to_string(), exec.path.to_owned())])
+                            )
+                        )
+                    );
+                    print!("{}", String::from_utf8_lossy(&stdout));
+                    eprint!("{}", String::from_utf8_lossy(&stderr));
+                }
+                (!status.success()).then(|| status.to_string())
+            }
+            Err(err) => Some(err.to_string()),
+        };
+        if let (Some(error), ExecOutput::Interleaved | ExecOutput::Grouped) = (&error, output) {
+            eprintln!(
+                "{}",
+                self.painter.paint(
+                    Paint::Error,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-exec-failed",
+                        Some(vec![
+                            ("repo".to_string(), exec.path.to_owned()),
+                            ("error".to_string(), error.to_owned()),
+                        ])
+                    )
+                )
+            );
+        }
+        (exec.path, exec.log_file, error)
+    }
+}
+
+/// Runs `child`, prefixing its lines with `path` when interleaved, capturing
+/// its output when grouped and writing it to `log_file` otherwise.
+async fn run(
+    mut child: Command,
+    path: String,
+    output: ExecOutput,
+    log_file: Option<PathBuf>,
+) -> ExecResult {
+    let result = async {
+        child.kill_on_drop(true).stdin(Stdio::null());
+        match output {
+            ExecOutput::Interleaved => {
+                let mut child = child
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                let stdout = child.stdout.take().map(BufReader::new);
+                let stderr = child.stderr.take().map(BufReader::new);
+                let (status, _, _) = tokio::join!(
+                    child.wait(),
+                    prefix_lines(stdout, &path, false),
+                    prefix_lines(stderr, &path, true)
+                );
+                Ok((status?, vec![], vec![]))
+            }
+            ExecOutput::Grouped => {
+                let output = child
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .output()
+                    .await?;
+                Ok((output.status, output.stdout, output.stderr))
+            }
+            ExecOutput::Files => {
+                let log_file = log_file
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("missing log file"))?;
+                if let Some(parent) = log_file.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                let log = std::fs::File::create(log_file)?;
+                let status = child.stdout(log.try_clone()?).stderr(log).status().await?;
+                Ok((status, vec![], vec![]))
+            }
+        }
+    }
+    .await;
+    ExecResult {
+        path,
+        log_file,
+        result,
+    }
+}
+
+async fn prefix_lines(reader: Option<BufReader<impl AsyncRead + Unpin>>, path: &str, stderr: bool) {
+    let Some(reader) = reader else {
+        return;
+    };
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if stderr {
+            eprintln!("[{}] {}", path, line);
+        } else {
+            println!("[{}] {}", path, line);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::anyhow;
+use tabled::Tabled;
+use toml_edit::DocumentMut;
+
+use crate::{
+    cli::{GroupCommands, RepoFilter},
+    style::Paint,
+};
+
+use super::Rer;
+
+#[derive(Tabled)]
+struct GroupTableItem {
+    group: String,
+    path: String,
+    hostname: String,
+}
+
+impl Rer {
+    pub(super) async fn group(&self, commands: &GroupCommands) -> anyhow::Result<()> {
+        match commands {
+            GroupCommands::List => {
+                let Some(groups) = self
+                    .config
+                    .groups
+                    .as_ref()
+                    .filter(|groups| !groups.is_empty())
+                else {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n
+                                .format_msg_or_log(&self.lang_id, "info-no-groups", None)
+                        )
+                    );
+                    return Ok(());
+                };
+                let repos = self.scan_repos(&RepoFilter::default()).await?;
+                let mut list = vec![];
+                for name in groups.keys() {
+                    for repo in self.group_members(name, &repos).await? {
+                        list.push(GroupTableItem {
+                            group: name.to_owned(),
+                            path: repo.path,
+                            hostname: repo.hostname,
+                        });
+                    }
+                }
+                println!("{}", self.render_table(tabled::Table::new(list)));
+                Ok(())
+            }
+            GroupCommands::Add { name, targets } => self.add_to_group(name, targets).await,
+        }
+    }
+
+    /// Appends `targets` to the targets of the group `name` in the config
+    /// file, keeping its formatting and comments.
+    async fn add_to_group(&self, name: &str, targets: &[String]) -> anyhow::Result<()> {
+        if let Some(target) = targets.iter().find(|target| target.starts_with('%')) {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-nested-group",
+                    Some(vec![
+                        ("group".to_string(), name.to_string()),
+                        ("target".to_string(), target.to_string()),
+                    ])
+                )
+            ));
+        }
+        let config_file = self.config_file()?;
+        let mut document = match tokio::fs::read_to_string(&config_file).await {
+            Ok(config) => config.parse::<DocumentMut>()?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let groups = document
+            .entry("groups")
+            .or_insert_with(|| {
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(true);
+                toml_edit::Item::Table(table)
+            })
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("groups is not a table"))?;
+        let group = groups
+            .entry(name)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("groups.{name} is not a table"))?;
+        let existing = group
+            .entry("targets")
+            .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+            .as_array_mut()
+            .ok_or_else(|| anyhow!("groups.{name}.targets is not an array"))?;
+        for target in targets {
+            if !existing.iter().any(|value| value.as_str() == Some(target)) {
+                existing.push(target.as_str());
+            }
+        }
+        if let Some(parent) = config_file.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&config_file, document.to_string()).await?;
+        println!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "info-group-added",
+                    Some(vec![
+                        ("group".to_string(), name.to_string()),
+                        ("targets".to_string(), targets.join(", ")),
+                        (
+                            "file".to_string(),
+                            config_file.to_string_lossy().to_string()
+                        ),
+                    ])
+                )
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::{path::Path, process::Stdio};
+
+use anyhow::anyhow;
+
+use crate::{layout, script::Script, style::Paint};
+
+use super::Rer;
+
+#[derive(Clone, Copy)]
+pub(super) enum Hook {
+    PostClone,
+    PostCreate,
+}
+
+impl Hook {
+    fn name(&self) -> &'static str {
+        match self {
+            Hook::PostClone => "post_clone",
+            Hook::PostCreate => "post_create",
+        }
+    }
+}
+
+impl Rer {
+    /// Runs the configured `hook` for the repository in `dir`, printing
+    /// failures without failing the command that triggered it.
+    pub(super) async fn run_hook(&self, hook: Hook, dir: &Path) {
+        let hooks = self.config.hooks.as_ref();
+        let command = match hook {
+            Hook::PostClone => hooks.and_then(|hooks| hooks.post_clone.as_deref()),
+            Hook::PostCreate => hooks.and_then(|hooks| hooks.post_create.as_deref()),
+        };
+        let Some(command) = command else {
+            return;
+        };
+        if let Err(err) = self.run_hook_command(command, dir).await {
+            eprintln!(
+                "{}",
+                self.painter.paint(
+                    Paint::Error,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-hook-failed",
+                        Some(vec![
+                            ("hook".to_string(), hook.name().to_string()),
+                            ("dir".to_string(), dir.to_string_lossy().to_string()),
+                            ("error".to_string(), err.to_string()),
+                        ])
+                    )
+                )
+            );
+        }
+    }
+
+    /// Runs a rhai script if `command` is a `.rhai` file, and a shell command
+    /// with the repository in `RERMAN_*` variables otherwise.
+    pub(super) async fn run_hook_command(&self, command: &str, dir: &Path) -> anyhow::Result<()> {
+        let repo = self
+            .repo_at(dir)
+            .await?
+            .ok_or_else(|| anyhow!("not inside a root"))?;
+        if command.ends_with(".rhai") {
+            return Script::load(&layout::expand_home(command))?.run(&repo, dir);
+        }
+        let mut child = if cfg!(target_os = "windows") {
+            let mut child = tokio::process::Command::new("cmd");
+            child.arg("/C");
+            child
+        } else {
+            let mut child = tokio::process::Command::new("sh");
+            child.arg("-c");
+            child
+        };
+        // hooks of a cancelled clone are killed with it
+        let status = child
+            .kill_on_drop(true)
+            .arg(command)
+            .current_dir(dir)
+            .env("RERMAN_TYPE", &repo.ty)
+            .env("RERMAN_HOSTNAME", &repo.hostname)
+            .env("RERMAN_OWNER", &repo.owner)
+            .env("RERMAN_PATH", &repo.path)
+            .env("RERMAN_DIR", dir)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .await?;
+        if !status.success() {
+            return Err(anyhow!("{}", status));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use log::debug;
+
+use crate::{
+    git, layout,
+    meta::{filter_repo_paths_recursively, meta_file, META_FILE},
+    style::Paint,
+    toolchain,
+};
+
+use super::{repo_item, RepoTableItem, Rer};
+
+const INDEX_FILE: &str = "index.json";
+/// Raised when the fields of repositories or the files they are taken from
+/// change, so indexes of older versions are rebuilt.
+const INDEX_VERSION: u32 = 3;
+
+/// Files of the repository in `dir` its fields in the index are taken from.
+fn repo_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![
+        meta_file(dir),
+        git::head_file(dir),
+        dir.join(".devcontainer"),
+        dir.join(".devcontainer.json"),
+    ];
+    files.extend(toolchain::MARKERS.iter().map(|(file, _)| dir.join(file)));
+    files
+}
+
+/// The repositories found by the last walk of the roots, with the
+/// modification times of everything their fields were taken from, to tell
+/// whether it still agrees with the file system.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct RepoIndex {
+    #[serde(default)]
+    version: u32,
+    roots: Vec<(String, PathBuf)>,
+    stamps: BTreeMap<PathBuf, Option<SystemTime>>,
+    repos: Vec<RepoTableItem>,
+}
+
+/// When `path` was last modified, `None` if it does not exist.
+fn stamp(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Whether `path` is one of [`repo_files`] of its repository.
+fn is_repo_file(path: &Path) -> bool {
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    let dir = match dir.file_name() {
+        Some(name) if name == ".git" => dir.parent().unwrap_or(dir),
+        _ => dir,
+    };
+    repo_files(dir).iter().any(|file| file == path)
+}
+
+/// The directories whose entries the index was built from, the ones looked
+/// into while walking and those of the repositories.
+fn watched_dirs(index: &RepoIndex) -> Vec<PathBuf> {
+    let mut dirs = index
+        .stamps
+        .keys()
+        .filter_map(|path| {
+            if is_repo_file(path) {
+                path.parent().map(Path::to_path_buf)
+            } else {
+                Some(path.to_owned())
+            }
+        })
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+impl Rer {
+    /// Walks the roots for all repositories, saving them as the index.
+    pub(super) async fn walk_repos(&self) -> anyhow::Result<Vec<RepoTableItem>> {
+        Ok(self.walk_index().await?.repos)
+    }
+
+    async fn walk_index(&self) -> anyhow::Result<RepoIndex> {
+        let roots = self.layout()?.roots()?;
+        let mut visited = vec![];
+        let mut repos = vec![];
+        for (ty, type_dir_path) in &roots {
+            visited.push(type_dir_path.to_owned());
+            for host_dir in std::fs::read_dir(type_dir_path)? {
+                let host_dir_path = host_dir?.path();
+                let host = host_dir_path
+                    .strip_prefix(type_dir_path)?
+                    .to_string_lossy()
+                    .to_string();
+                let marker = match ty.as_str() {
+                    "git" => ".git",
+                    _ => META_FILE,
+                };
+                for repo_dir in
+                    filter_repo_paths_recursively(&host_dir_path, marker, &mut visited).await?
+                {
+                    let relative = repo_dir.strip_prefix(&host_dir_path)?;
+                    let owner = layout::owner_of(relative);
+                    let repo_path = relative.to_string_lossy().to_string();
+                    visited.extend(repo_files(&repo_dir));
+                    repos.push(repo_item(ty, &host, &owner, &repo_path, &repo_dir).await);
+                }
+            }
+        }
+        let index = RepoIndex {
+            version: INDEX_VERSION,
+            roots,
+            stamps: visited
+                .into_iter()
+                .map(|path| {
+                    let stamp = stamp(&path);
+                    (path, stamp)
+                })
+                .collect(),
+            repos,
+        };
+        // the index only speeds up later lists, failing to save it is no error
+        if let Err(err) = self.save_index(&index).await {
+            debug!("could not save the index: {:?}", err);
+        }
+        Ok(index)
+    }
+
+    /// All repositories, from the index if it agrees with the file system and
+    /// from a walk of the roots otherwise, warning when the index was stale.
+    pub(super) async fn indexed_repos(&self) -> anyhow::Result<Vec<RepoTableItem>> {
+        let index = tokio::fs::read_to_string(self.data_dir()?.join(INDEX_FILE))
+            .await
+            .ok()
+            .and_then(|index| serde_json::from_str::<RepoIndex>(&index).ok());
+        let Some(index) = index else {
+            return self.walk_repos().await;
+        };
+        // other roots mean another config rather than changed repositories
+        if index.version != INDEX_VERSION || index.roots != self.layout()?.roots()? {
+            return self.walk_repos().await;
+        }
+        if index
+            .stamps
+            .iter()
+            .all(|(path, modified)| stamp(path) == *modified)
+        {
+            return Ok(index.repos);
+        }
+        eprintln!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n
+                    .format_msg_or_log(&self.lang_id, "info-index-stale", None)
+            )
+        );
+        self.walk_repos().await
+    }
+
+    /// Walks the roots for the index, returning the directories whose changes
+    /// it depends on.
+    pub(super) async fn reindex(&self) -> anyhow::Result<Vec<PathBuf>> {
+        Ok(watched_dirs(&self.walk_index().await?))
+    }
+
+    /// Brings the index up to date after `changed` paths changed, only
+    /// reading the repositories again whose files changed, unless a change is
+    /// outside of them and repositories may have appeared or disappeared.
+    /// Returns the directories whose changes the index depends on.
+    pub(super) async fn update_index(&self, changed: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+        let index = tokio::fs::read_to_string(self.data_dir()?.join(INDEX_FILE))
+            .await
+            .ok()
+            .and_then(|index| serde_json::from_str::<RepoIndex>(&index).ok());
+        let mut index = match index {
+            Some(index)
+                if index.version == INDEX_VERSION && index.roots == self.layout()?.roots()? =>
+            {
+                index
+            }
+            _ => self.walk_index().await?,
+        };
+        let repo_dirs = index
+            .repos
+            .iter()
+            .map(|repo| self.dir_of(repo))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut refreshed = vec![];
+        for path in changed {
+            // the watched directories in repositories are theirs and their .git
+            let in_repo = path.parent().and_then(|dir| {
+                repo_dirs
+                    .iter()
+                    .position(|repo_dir| repo_dir == dir || repo_dir.join(".git") == dir)
+            });
+            match in_repo {
+                // other files of repositories are not in the index
+                Some(repo) if is_repo_file(path) => refreshed.push(repo),
+                Some(_) => {}
+                None if repo_dirs.contains(path) => {}
+                None => return self.reindex().await,
+            }
+        }
+        if refreshed.is_empty() {
+            return Ok(watched_dirs(&index));
+        }
+        for repo in refreshed {
+            let item = &index.repos[repo];
+            let dir = &repo_dirs[repo];
+            index.repos[repo] =
+                repo_item(&item.ty, &item.hostname, &item.owner, &item.path, dir).await;
+            for path in repo_files(dir) {
+                let stamp = stamp(&path);
+                index.stamps.insert(path, stamp);
+            }
+        }
+        self.save_index(&index).await?;
+        Ok(watched_dirs(&index))
+    }
+
+    async fn save_index(&self, index: &RepoIndex) -> anyhow::Result<()> {
+        let data_dir = self.data_dir()?;
+        tokio::fs::create_dir_all(&data_dir).await?;
+        tokio::fs::write(data_dir.join(INDEX_FILE), serde_json::to_string(index)?).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use std::io::IsTerminal;
+
+use anyhow::anyhow;
+use dialoguer::{Input, Select};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::{git::Protocol, style::Paint};
+
+use super::{Rer, RerSetup};
+
+const CONFIG_TEMPLATE: &str = include_str!("../../assets/config.toml");
+
+impl Rer {
+    /// Writes the config and creates the repository directory, asking for the
+    /// basic settings when run in a terminal. An existing config only gets the
+    /// missing settings added unless `force` is given, and is backed up before
+    /// it is changed.
+    pub(super) async fn init(&self, force: bool) -> anyhow::Result<()> {
+        let (setup, repo_dir, config) =
+            if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+                self.ask_init()?
+            } else {
+                (
+                    self.setup.clone(),
+                    self.repo_dir()?,
+                    CONFIG_TEMPLATE.to_string(),
+                )
+            };
+
+        let config_file = self.config_file_of(&setup)?;
+        let config_dir = config_file.parent().ok_or_else(|| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-invalid-config-dir",
+                    Some(vec![(
+                        "dir".to_string(),
+                        config_file.join("..").to_string_lossy().to_string()
+                    )])
+                )
+            )
+        })?;
+        if config_dir.exists() && !config_dir.is_dir() {
+            Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-invalid-config-dir",
+                    Some(vec![(
+                        "dir".to_string(),
+                        config_dir.to_string_lossy().to_string()
+                    )])
+                )
+            ))?
+        }
+        if !config_dir.exists() {
+            tokio::fs::create_dir_all(config_dir).await?;
+        }
+
+        if repo_dir.exists() && !repo_dir.is_dir() {
+            Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-invalid-repo-dir",
+                    Some(vec![(
+                        "dir".to_string(),
+                        repo_dir.to_string_lossy().to_string()
+                    )])
+                )
+            ))?
+        }
+        if !repo_dir.exists() {
+            tokio::fs::create_dir_all(&repo_dir).await?;
+        }
+
+        if config_file.exists() {
+            let previous = tokio::fs::read_to_string(&config_file).await?;
+            let config = if force {
+                config
+            } else {
+                let mut document = previous.parse::<DocumentMut>()?;
+                let mut changes = vec![];
+                merge_missing(
+                    document.as_table_mut(),
+                    config.parse::<DocumentMut>()?.as_table(),
+                    "",
+                    &mut changes,
+                );
+                for (key, added) in &changes {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                if *added {
+                                    "info-config-key-added"
+                                } else {
+                                    "info-config-key-kept"
+                                },
+                                Some(vec![("key".to_string(), key.to_owned())])
+                            )
+                        )
+                    );
+                }
+                if !changes.iter().any(|(_, added)| *added) {
+                    println!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Info,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "info-config-unchanged",
+                                Some(vec![(
+                                    "file".to_string(),
+                                    config_file.to_string_lossy().to_string()
+                                )])
+                            )
+                        )
+                    );
+                    return Ok(());
+                }
+                document.to_string()
+            };
+            let mut backup_file = config_file.clone().into_os_string();
+            backup_file.push(".bak");
+            tokio::fs::write(&backup_file, previous).await?;
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-config-backup",
+                        Some(vec![(
+                            "file".to_string(),
+                            backup_file.to_string_lossy().to_string()
+                        )])
+                    )
+                )
+            );
+            tokio::fs::write(&config_file, config.as_bytes()).await?;
+        } else {
+            tokio::fs::write(&config_file, config.as_bytes()).await?;
+        }
+        println!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "info-setup-completed",
+                    Some(vec![(
+                        "file".to_string(),
+                        config_file.to_string_lossy().to_string()
+                    )])
+                )
+            )
+        );
+        Ok(())
+    }
+
+    /// Asks for the level, repository directory, opener, default host and
+    /// clone protocol, and fills them into the config template.
+    fn ask_init(&self) -> anyhow::Result<(RerSetup, std::path::PathBuf, String)> {
+        let prompt = |key: &str| self.i18n.format_msg_or_log(&self.lang_id, key, None);
+        let explicit = self.cli.system.is_some()
+            || self.cli.user.is_some()
+            || self.cli.local.is_some()
+            || self.cli.config.is_some();
+        let setup = if explicit {
+            self.setup.clone()
+        } else {
+            match Select::new()
+                .with_prompt(prompt("prompt-init-level"))
+                .items(&[prompt("init-level-user"), prompt("init-level-local")])
+                .default(0)
+                .interact()?
+            {
+                0 => RerSetup::User,
+                _ => RerSetup::Local,
+            }
+        };
+        let repo_dir: String = Input::new()
+            .with_prompt(prompt("prompt-init-repo-dir"))
+            .default(self.repo_dir_of(&setup)?.to_string_lossy().to_string())
+            .interact_text()?;
+        let open_with: String = Input::new()
+            .with_prompt(prompt("prompt-init-open-with"))
+            .default(self.config.open_with.to_owned().unwrap_or_default())
+            .allow_empty(true)
+            .show_default(self.config.open_with.is_some())
+            .interact_text()?;
+        let default_host: String = Input::new()
+            .with_prompt(prompt("prompt-init-default-host"))
+            .default(
+                self.config
+                    .default_host
+                    .to_owned()
+                    .unwrap_or_else(|| "localhost".to_string()),
+            )
+            .interact_text()?;
+        let protocol = match Select::new()
+            .with_prompt(prompt("prompt-init-protocol"))
+            .items(&["https", "ssh"])
+            .default(match self.config.protocol {
+                Some(Protocol::Ssh) => 1,
+                _ => 0,
+            })
+            .interact()?
+        {
+            0 => "https",
+            _ => "ssh",
+        };
+
+        let mut values = vec![
+            ("repo_dir", repo_dir.to_owned()),
+            ("default_host", default_host),
+            ("protocol", protocol.to_string()),
+        ];
+        if !open_with.is_empty() {
+            values.push(("open_with", open_with));
+        }
+        Ok((
+            setup,
+            repo_dir.into(),
+            fill_template(CONFIG_TEMPLATE, &values),
+        ))
+    }
+}
+
+/// Adds the keys of `new` missing in `config`, recording them as added and
+/// those with a different value as kept.
+fn merge_missing(config: &mut Table, new: &Table, prefix: &str, changes: &mut Vec<(String, bool)>) {
+    for (key, item) in new.iter() {
+        let path = format!("{}{}", prefix, key);
+        if path == "version" {
+            // adding it would skip the migrations the config still needs
+            continue;
+        }
+        match (config.get_mut(key), item) {
+            (None, _) => {
+                config.insert(key, item.clone());
+                changes.push((path, true));
+            }
+            (Some(Item::Table(config)), Item::Table(new)) => {
+                merge_missing(config, new, &format!("{}.", path), changes)
+            }
+            (Some(existing), _) => {
+                if existing.to_string().trim() != item.to_string().trim() {
+                    changes.push((path, false));
+                }
+            }
+        }
+    }
+}
+
+/// Uncomments the top level `# key = ...` lines of `template` for the given
+/// keys, setting them to the given values.
+fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut top_level = true;
+    template
+        .lines()
+        .map(|line| {
+            top_level &= !line.starts_with("# [");
+            let key = line
+                .strip_prefix("# ")
+                .and_then(|line| line.split_once(" = "))
+                .map(|(key, _)| key)
+                .filter(|_| top_level);
+            match values.iter().find(|(name, _)| Some(*name) == key) {
+                Some((name, value)) => {
+                    format!("{} = {}", name, toml::Value::String(value.to_owned()))
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
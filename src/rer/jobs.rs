@@ -0,0 +1,170 @@
+use std::{
+    collections::BTreeMap,
+    time::{Instant, SystemTime},
+};
+
+use anyhow::anyhow;
+use tabled::Tabled;
+
+use crate::{cli::JobsCommands, style::Paint};
+
+use super::Rer;
+
+const JOBS_FILE: &str = "jobs.json";
+
+/// The last run of a job, kept in the data directory.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct JobRun {
+    /// Seconds since the unix epoch.
+    started: u64,
+    seconds: u64,
+    success: bool,
+}
+
+#[derive(Tabled)]
+struct JobTableItem {
+    job: String,
+    schedule: String,
+    command: String,
+    last_run: String,
+    status: String,
+}
+
+impl Rer {
+    pub(super) async fn jobs(&self, commands: &JobsCommands) -> anyhow::Result<()> {
+        match commands {
+            JobsCommands::List => {
+                let runs = self.job_runs().await;
+                let list = self
+                    .config
+                    .jobs
+                    .iter()
+                    .flatten()
+                    .map(|(name, job)| {
+                        let run = runs.get(name);
+                        JobTableItem {
+                            job: name.to_owned(),
+                            schedule: job.schedule.to_owned().unwrap_or_default(),
+                            command: job.command.to_owned(),
+                            last_run: run.map(|run| ago(run.started)).unwrap_or_default(),
+                            status: run
+                                .map(|run| if run.success { "ok" } else { "failed" }.to_string())
+                                .unwrap_or_default(),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", self.render_table(tabled::Table::new(list)));
+                Ok(())
+            }
+            JobsCommands::Run { name } => self.run_job(name).await,
+        }
+    }
+
+    /// Runs the job `name` as another rerman with the same config, recording
+    /// how it went.
+    async fn run_job(&self, name: &str) -> anyhow::Result<()> {
+        let params = |key: &str, value: String| {
+            Some(vec![
+                ("job".to_string(), name.to_string()),
+                (key.to_string(), value),
+            ])
+        };
+        let job = self
+            .config
+            .jobs
+            .as_ref()
+            .and_then(|jobs| jobs.get(name))
+            .ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-unknown-job",
+                        Some(vec![("job".to_string(), name.to_string())])
+                    )
+                )
+            })?;
+        let args = shell_words::split(&job.command).map_err(|err| {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-invalid-job",
+                    params("error", err.to_string())
+                )
+            )
+        })?;
+        let started = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs();
+        let clock = Instant::now();
+        let mut command = tokio::process::Command::new(std::env::current_exe()?);
+        command.arg("--config").arg(self.config_file()?);
+        if let Some(timeout) = self.cli.timeout {
+            command.arg("--timeout").arg(timeout.to_string());
+        }
+        // the job is killed with rerman when it is interrupted
+        let status = command.args(&args).kill_on_drop(true).status().await?;
+        let mut runs = self.job_runs().await;
+        runs.insert(
+            name.to_string(),
+            JobRun {
+                started,
+                seconds: clock.elapsed().as_secs(),
+                success: status.success(),
+            },
+        );
+        let data_dir = self.data_dir()?;
+        tokio::fs::create_dir_all(&data_dir).await?;
+        tokio::fs::write(data_dir.join(JOBS_FILE), serde_json::to_string(&runs)?).await?;
+        if !status.success() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-job-failed",
+                    params("status", status.to_string())
+                )
+            ));
+        }
+        println!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "info-job-done",
+                    params("seconds", clock.elapsed().as_secs().to_string())
+                )
+            )
+        );
+        Ok(())
+    }
+
+    /// The last runs of the jobs, none if they cannot be read.
+    async fn job_runs(&self) -> BTreeMap<String, JobRun> {
+        let Ok(data_dir) = self.data_dir() else {
+            return BTreeMap::new();
+        };
+        tokio::fs::read_to_string(data_dir.join(JOBS_FILE))
+            .await
+            .ok()
+            .and_then(|runs| serde_json::from_str(&runs).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// How long ago `time`, in seconds since the unix epoch, was, roughly.
+fn ago(time: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    let seconds = now.saturating_sub(time);
+    match seconds {
+        0..60 => format!("{}s ago", seconds),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
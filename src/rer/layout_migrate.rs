@@ -0,0 +1,122 @@
+use anyhow::anyhow;
+
+use crate::{
+    audit::{self, AuditEvent},
+    cli::RepoFilter,
+    fs,
+    git::GitUrl,
+    style::Paint,
+};
+
+use super::Rer;
+
+impl Rer {
+    /// Moves git repositories that are not in the directory of their origin
+    /// url there, or only prints them without `apply`. Moves are recorded in
+    /// the audit log.
+    pub(super) async fn migrate_layout(
+        &self,
+        filter: &RepoFilter,
+        apply: bool,
+    ) -> anyhow::Result<()> {
+        let git = self.git();
+        let mut failed = 0;
+        let mut misplaced = 0;
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            // repositories without an origin have no other place to be
+            let Ok(url) = git.remote_url(&dir, "origin").await else {
+                continue;
+            };
+            let target = match GitUrl::parse(&url) {
+                Ok(parsed) => self.path_of_git_url(&parsed)?,
+                Err(_) => continue,
+            };
+            if target == dir {
+                continue;
+            }
+            misplaced += 1;
+            let params = |key: &str, value: String| {
+                Some(vec![
+                    ("repo".to_string(), repo.path.to_owned()),
+                    (key.to_string(), value),
+                ])
+            };
+            let target_str = target.to_string_lossy().to_string();
+            if !apply {
+                println!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-layout-would-move",
+                        params("dir", target_str)
+                    )
+                );
+                continue;
+            }
+            let moved = async {
+                fs::move_dir(&dir, &target).await?;
+                audit::record(
+                    &self.audit_file()?,
+                    &AuditEvent::Move {
+                        url,
+                        from_dir: dir.to_string_lossy().to_string(),
+                        to_dir: target_str.to_owned(),
+                    },
+                )
+                .await
+            };
+            match moved.await {
+                Ok(()) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-layout-moved",
+                            params("dir", target_str)
+                        )
+                    )
+                ),
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-layout-move-failed",
+                                params("error", err.to_string())
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        if misplaced == 0 {
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n
+                        .format_msg_or_log(&self.lang_id, "info-layout-in-place", None)
+                )
+            );
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::anyhow;
+use log::{debug, warn};
+use tabled::Tabled;
+
+use crate::{
+    cli::{ManifestCommands, RepoFilter},
+    fs::PartialDir,
+    git::GitUrl,
+    layout,
+    manifest::{Manifest, ManifestFormat, ManifestRepo},
+    meta::RepoMeta,
+    queue::{CloneQueue, QueueState},
+    style::Paint,
+    vendor,
+};
+
+use super::{hooks::Hook, Rer};
+
+/// How a repository differs from a manifest.
+#[derive(serde::Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Drift {
+    /// In the manifest but not cloned.
+    Missing,
+    /// Cloned but not in the manifest.
+    Extra,
+    /// Cloned from another url than that of the manifest.
+    Url,
+    /// At another commit than that of the manifest.
+    Commit,
+}
+
+impl Drift {
+    fn name(&self) -> &'static str {
+        match self {
+            Drift::Missing => "missing",
+            Drift::Extra => "extra",
+            Drift::Url => "url",
+            Drift::Commit => "commit",
+        }
+    }
+}
+
+#[derive(serde::Serialize, Tabled)]
+struct DriftItem {
+    #[serde(rename = "type")]
+    #[tabled(rename = "type")]
+    ty: String,
+    hostname: String,
+    path: String,
+    #[tabled(display_with = "Drift::name")]
+    drift: Drift,
+    /// The url or commit of the manifest.
+    #[tabled(display_with = "display_option")]
+    manifest: Option<String>,
+    /// The url or commit of the local repository.
+    #[tabled(display_with = "display_option")]
+    local: Option<String>,
+}
+
+fn display_option(value: &Option<String>) -> String {
+    value.to_owned().unwrap_or_default()
+}
+
+impl Rer {
+    pub(super) async fn manifest(&self, commands: &ManifestCommands) -> anyhow::Result<()> {
+        match commands {
+            ManifestCommands::Export {
+                format,
+                commits,
+                filter,
+            } => {
+                let git = self.git();
+                let mut manifest = Manifest::default();
+                for repo in self.scan_repos(filter).await? {
+                    let dir = self.dir_of(&repo)?;
+                    let url = match repo.ty.as_str() {
+                        "git" => git.remote_url(&dir, "origin").await.ok(),
+                        _ => RepoMeta::load(&dir).await?.vendor.map(|vendor| vendor.url),
+                    };
+                    let Some(url) = url else {
+                        warn!("{} has no url to clone it from, skipped", repo.path);
+                        continue;
+                    };
+                    let commit = match (*commits, repo.ty.as_str()) {
+                        (true, "git") => git.head(&dir).await.map(|head| head.trim().to_string()),
+                        _ => None,
+                    };
+                    manifest.repos.push(ManifestRepo {
+                        ty: repo.ty,
+                        hostname: repo.hostname,
+                        path: repo.path,
+                        url,
+                        commit,
+                    });
+                }
+                print!("{}", manifest.export(*format)?);
+                Ok(())
+            }
+            ManifestCommands::Import { file, format } => {
+                let manifest = Manifest::import(&tokio::fs::read_to_string(file).await?, *format)?;
+                self.run_queue(CloneQueue::new(manifest.repos)).await
+            }
+        }
+    }
+
+    /// Reports how the repositories matching `filter` differ from the
+    /// manifest in `file`, failing if they do, without changing anything.
+    /// Urls are compared by the repository they point to, so an ssh clone of
+    /// an https url agrees with it.
+    pub(super) async fn diff_manifest(
+        &self,
+        file: &str,
+        format: Option<ManifestFormat>,
+        filter: &RepoFilter,
+        json: bool,
+    ) -> anyhow::Result<()> {
+        let manifest = Manifest::import(&tokio::fs::read_to_string(file).await?, format)?;
+        let mut local = self
+            .scan_repos(filter)
+            .await?
+            .into_iter()
+            .map(|repo| {
+                (
+                    (repo.ty.clone(), repo.hostname.clone(), repo.path.clone()),
+                    repo,
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let git = self.git();
+        let mut drifts = vec![];
+        for expected in manifest.repos {
+            let key = (
+                expected.ty.clone(),
+                expected.hostname.clone(),
+                expected.path.clone(),
+            );
+            let item = |drift, manifest, local| DriftItem {
+                ty: expected.ty.clone(),
+                hostname: expected.hostname.clone(),
+                path: expected.path.clone(),
+                drift,
+                manifest,
+                local,
+            };
+            let Some(repo) = local.remove(&key) else {
+                drifts.push(item(Drift::Missing, Some(expected.url.clone()), None));
+                continue;
+            };
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            let url = git.remote_url(&dir, "origin").await.ok();
+            let same_repo = |url: &str| match (GitUrl::parse(url), GitUrl::parse(&expected.url)) {
+                (Ok(url), Ok(expected)) => url.canonical() == expected.canonical(),
+                _ => url == expected.url,
+            };
+            if !url.as_deref().is_some_and(same_repo) {
+                drifts.push(item(Drift::Url, Some(expected.url.clone()), url));
+            }
+            if let Some(ref commit) = expected.commit {
+                let head = git.head(&dir).await.map(|head| head.trim().to_string());
+                // manifests may have abbreviated commits
+                if !head
+                    .as_deref()
+                    .is_some_and(|head| head.starts_with(commit.as_str()))
+                {
+                    drifts.push(item(Drift::Commit, Some(commit.to_owned()), head));
+                }
+            }
+        }
+        for repo in local.into_values() {
+            drifts.push(DriftItem {
+                ty: repo.ty,
+                hostname: repo.hostname,
+                path: repo.path,
+                drift: Drift::Extra,
+                manifest: None,
+                local: None,
+            });
+        }
+        if json {
+            println!("{}", serde_json::to_string(&drifts)?);
+        } else if drifts.is_empty() {
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n
+                        .format_msg_or_log(&self.lang_id, "info-manifest-in-sync", None)
+                )
+            );
+        } else {
+            println!("{}", self.render_table(tabled::Table::new(&drifts)));
+        }
+        if !drifts.is_empty() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-manifest-drift",
+                    Some(vec![("count".to_string(), drifts.len().to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Resumes the last interrupted bulk clone.
+    pub(super) async fn resume(&self) -> anyhow::Result<()> {
+        match CloneQueue::load(&self.queue_file()?).await? {
+            Some(queue) => self.run_queue(queue).await,
+            None => {
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n
+                            .format_msg_or_log(&self.lang_id, "info-nothing-to-resume", None)
+                    )
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn queue_file(&self) -> anyhow::Result<PathBuf> {
+        Ok(self.data_dir()?.join("clone-queue.toml"))
+    }
+
+    /// Clones the items of `queue` not done yet, saving it after each so that
+    /// `resume` picks up where an interrupted run stopped.
+    async fn run_queue(&self, mut queue: CloneQueue) -> anyhow::Result<()> {
+        let queue_file = self.queue_file()?;
+        queue.save(&queue_file).await?;
+        let mut failed = 0;
+        for index in 0..queue.items.len() {
+            if queue.items[index].state == QueueState::Done {
+                continue;
+            }
+            let repo = &queue.items[index].repo;
+            let name = format!("{}/{}", repo.hostname, repo.path);
+            let params = |error: Option<String>| {
+                let mut params = vec![("repo".to_string(), name.to_owned())];
+                params.extend(error.map(|error| ("error".to_string(), error)));
+                Some(params)
+            };
+            let result = self.import_repo(repo).await;
+            match result {
+                Ok(cloned) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            if cloned {
+                                "info-repo-cloned"
+                            } else {
+                                "info-repo-exists"
+                            },
+                            params(None)
+                        )
+                    )
+                ),
+                Err(ref err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-repo-clone-failed",
+                                params(Some(err.to_string()))
+                            )
+                        )
+                    );
+                }
+            }
+            let item = &mut queue.items[index];
+            (item.state, item.error) = match result {
+                Ok(_) => (QueueState::Done, None),
+                Err(err) => (QueueState::Failed, Some(err.to_string())),
+            };
+            queue.save(&queue_file).await?;
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed-resume",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Clones one repository of a manifest, returns false if it already exists.
+    async fn import_repo(&self, repo: &ManifestRepo) -> anyhow::Result<bool> {
+        let (owner, name) = layout::split_path(&repo.path);
+        let dir = self.path_of_repo(&repo.ty, &repo.hostname, owner, name)?;
+        if dir.exists() {
+            return Ok(false);
+        }
+        match repo.ty.as_str() {
+            "git" => {
+                let partial = PartialDir::new(&dir);
+                let options = match GitUrl::parse(&repo.url) {
+                    Ok(url) => self.reference_options(&url)?,
+                    Err(_) => vec![],
+                };
+                let options = options.iter().map(String::as_str).collect::<Vec<_>>();
+                let status = self
+                    .git()
+                    .clone(&options, &repo.url, dir.to_string_lossy())
+                    .await?;
+                if !status.success() {
+                    return Err(anyhow!("git clone {}", status));
+                }
+                partial.keep();
+                if let Ok(url) = GitUrl::parse(&repo.url) {
+                    if let Err(err) = self.fetch_meta(&url, &dir).await {
+                        debug!("fetching metadata failed: {:?}", err);
+                    }
+                }
+            }
+            "vendor" => {
+                vendor::fetch(&repo.url, &dir).await?;
+            }
+            ty => {
+                self.clone_with_plugin(ty, &repo.url, Some(&dir)).await?;
+            }
+        }
+        self.record_cloned_by(&dir).await;
+        self.run_hook(Hook::PostClone, &dir).await;
+        Ok(true)
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::anyhow;
+
+use crate::{cli::MirrorCommands, style::Paint};
+
+use super::Rer;
+
+impl Rer {
+    pub(super) async fn mirror(&self, commands: &MirrorCommands) -> anyhow::Result<()> {
+        let git = self.git();
+        match commands {
+            MirrorCommands::Add { name, target, url } => {
+                let dir = self.find_repo(target).await?;
+                git.output(&dir, &["remote", "add", name, url]).await?;
+                Ok(())
+            }
+            MirrorCommands::Push { name, filter } => {
+                let mut failed = 0;
+                for repo in self.scan_repos(filter).await? {
+                    if repo.ty != "git" {
+                        continue;
+                    }
+                    let dir = self.dir_of(&repo)?;
+                    if !git.remotes(&dir).await?.contains(name) {
+                        continue;
+                    }
+                    let params = |error: Option<String>| {
+                        let mut params = vec![("repo".to_string(), repo.path.to_owned())];
+                        params.extend(error.map(|error| ("error".to_string(), error)));
+                        Some(params)
+                    };
+                    match git
+                        .output(
+                            &dir,
+                            &[
+                                "push",
+                                name,
+                                "refs/heads/*:refs/heads/*",
+                                "refs/tags/*:refs/tags/*",
+                            ],
+                        )
+                        .await
+                    {
+                        Ok(_) => println!(
+                            "{}",
+                            self.painter.paint(
+                                Paint::Info,
+                                self.i18n.format_msg_or_log(
+                                    &self.lang_id,
+                                    "info-mirror-pushed",
+                                    params(None)
+                                )
+                            )
+                        ),
+                        Err(err) => {
+                            failed += 1;
+                            eprintln!(
+                                "{}",
+                                self.painter.paint(
+                                    Paint::Error,
+                                    self.i18n.format_msg_or_log(
+                                        &self.lang_id,
+                                        "error-mirror-push-failed",
+                                        params(Some(err.to_string()))
+                                    )
+                                )
+                            );
+                        }
+                    }
+                }
+                if failed > 0 {
+                    return Err(anyhow!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-some-repos-failed",
+                            Some(vec![("count".to_string(), failed.to_string())])
+                        )
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+}
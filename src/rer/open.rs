@@ -0,0 +1,102 @@
+use std::{path::Path, process::Stdio};
+
+use anyhow::anyhow;
+use log::warn;
+
+use crate::{devcontainer, meta::RepoMeta};
+
+use super::Rer;
+
+impl Rer {
+    pub(super) async fn open(&self, with: Option<&str>, target: &str) -> anyhow::Result<()> {
+        let target_dir = self.find_repo(target).await?;
+        let mut meta = RepoMeta::load(&target_dir).await?;
+        if let (Some(with), true) = (with, self.config.remember_opener.unwrap_or(false)) {
+            if meta.opener.as_deref() != Some(with) {
+                meta.opener = Some(with.to_string());
+                if let Err(err) = meta.save(&target_dir).await {
+                    // a shared read-only root can still be opened from
+                    warn!("remembering the opener failed: {:?}", err);
+                }
+            }
+        }
+        let open_with = with
+            .map(str::to_string)
+            .or(meta.opener)
+            .or_else(|| self.config.open_with.to_owned())
+            .ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    self.i18n
+                        .format_msg_or_log(&self.lang_id, "error-no-default-open-with", None)
+                )
+            })?;
+        self.open_with(&open_with, &target_dir).await
+    }
+
+    pub(super) async fn open_devcontainer(&self, target: &str) -> anyhow::Result<()> {
+        let target_dir = self.find_repo(target).await?;
+        if !devcontainer::detect(&target_dir) {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-no-devcontainer",
+                    Some(vec![("repo".to_string(), target.to_string())])
+                )
+            ));
+        }
+        devcontainer::open(&target_dir).await
+    }
+
+    /// Opens `dir` with the opener profile named `open_with`, or the program
+    /// of that name if there is no such profile. Openers containing `://` are
+    /// URI templates handed to the platform URL handler.
+    async fn open_with(&self, open_with: &str, dir: &Path) -> anyhow::Result<()> {
+        let path = dir.to_string_lossy().to_string();
+        let (cmd, args) = match self
+            .config
+            .openers
+            .as_ref()
+            .and_then(|openers| openers.get(open_with))
+        {
+            Some(opener) if opener.cmd.contains("://") => uri_handler(&opener.cmd, &path),
+            None if open_with.contains("://") => uri_handler(open_with, &path),
+            Some(opener) => {
+                let mut args = opener
+                    .args
+                    .iter()
+                    .map(|arg| arg.replace("{path}", &path))
+                    .collect::<Vec<_>>();
+                if !opener.args.iter().any(|arg| arg.contains("{path}")) {
+                    args.push(path);
+                }
+                (opener.cmd.to_owned(), args)
+            }
+            None => (open_with.to_string(), vec![path]),
+        };
+        tokio::process::Command::new(cmd)
+            .args(args)
+            .stdout(Stdio::inherit())
+            .spawn()?
+            .wait()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Returns the platform URL handler invocation for the URI template `uri`,
+/// with `{path}` replaced by `path`.
+pub(super) fn uri_handler(uri: &str, path: &str) -> (String, Vec<String>) {
+    let uri = uri.replace("{path}", path);
+    if cfg!(target_os = "windows") {
+        (
+            "cmd".to_string(),
+            vec!["/C".to_string(), "start".to_string(), String::new(), uri],
+        )
+    } else if cfg!(target_os = "macos") {
+        ("open".to_string(), vec![uri])
+    } else {
+        ("xdg-open".to_string(), vec![uri])
+    }
+}
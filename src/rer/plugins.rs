@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+
+use crate::{
+    forge::RepoInfo,
+    fs::PartialDir,
+    layout,
+    meta::RepoMeta,
+    plugin::{self, Plugin},
+};
+
+use super::Rer;
+
+#[derive(serde::Deserialize)]
+struct PluginLocation {
+    hostname: String,
+    path: String,
+}
+
+#[derive(serde::Deserialize)]
+struct PluginUpdate {
+    updated: bool,
+}
+
+impl Rer {
+    fn plugins(&self) -> anyhow::Result<Vec<Plugin>> {
+        match self.config_file()?.parent() {
+            Some(config_dir) => plugin::discover(&config_dir.join("plugins")),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// The plugin providing the repository type `ty`.
+    fn plugin_of_type(&self, ty: &str) -> anyhow::Result<Plugin> {
+        self.plugins()?
+            .into_iter()
+            .find(|plugin| plugin.types.iter().any(|t| t == ty))
+            .ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-unknown-repo-type",
+                        Some(vec![("type".to_string(), ty.to_string())])
+                    )
+                )
+            })
+    }
+
+    /// Clones `url` with the plugin of `ty` into `dir`, or the directory the
+    /// plugin locates it in, returning the directory.
+    pub(super) async fn clone_with_plugin(
+        &self,
+        ty: &str,
+        url: &str,
+        dir: Option<&Path>,
+    ) -> anyhow::Result<PathBuf> {
+        let plugin = self.plugin_of_type(ty)?;
+        let dir = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => {
+                let location: PluginLocation = plugin
+                    .call_async(serde_json::json!({ "op": "locate", "type": ty, "url": url }))
+                    .await?;
+                let (owner, name) = layout::split_path(&location.path);
+                self.path_of_repo(ty, &location.hostname, owner, name)?
+            }
+        };
+        if dir.exists() {
+            return Err(anyhow!("destination already exists: {}", dir.display()));
+        }
+        let partial = PartialDir::new(&dir);
+        tokio::fs::create_dir_all(&dir).await?;
+        plugin
+            .call_async::<serde_json::Value>(serde_json::json!({
+                "op": "clone",
+                "type": ty,
+                "url": url,
+                "dir": dir,
+            }))
+            .await?;
+        // the metadata file marks the repository for scanning
+        let meta = RepoMeta {
+            url: Some(url.to_string()),
+            ..RepoMeta::load(&dir).await?
+        };
+        meta.save(&dir).await?;
+        partial.keep();
+        Ok(dir)
+    }
+
+    /// Updates the repository of type `ty` in `dir` with its plugin, returns
+    /// whether anything changed.
+    pub(super) async fn update_with_plugin(&self, ty: &str, dir: &Path) -> anyhow::Result<bool> {
+        let url = RepoMeta::load(dir).await?.url;
+        let update: PluginUpdate = self
+            .plugin_of_type(ty)?
+            .call_async(serde_json::json!({
+                "op": "update",
+                "type": ty,
+                "url": url,
+                "dir": dir,
+            }))
+            .await?;
+        Ok(update.updated)
+    }
+
+    /// Asks the plugin providing `forge` about the repository `project`.
+    pub(super) async fn repo_info_with_plugin(
+        &self,
+        forge: &str,
+        host: &str,
+        project: &str,
+    ) -> anyhow::Result<RepoInfo> {
+        let plugin = self
+            .plugins()?
+            .into_iter()
+            .find(|plugin| plugin.forges.iter().any(|f| f == forge))
+            .ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-unknown-forge",
+                        Some(vec![("forge".to_string(), forge.to_string())])
+                    )
+                )
+            })?;
+        plugin
+            .call_async(serde_json::json!({
+                "op": "repo_info",
+                "forge": forge,
+                "host": host,
+                "project": project,
+            }))
+            .await
+    }
+}
@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::{cli::RepoFilter, git::Git, style::Paint};
+
+use super::Rer;
+
+enum PushResult {
+    Pushed(String),
+    UpToDate(String),
+    /// The remote branch has commits the local one lacks.
+    Rejected(String),
+    Detached,
+}
+
+impl Rer {
+    /// Pushes the current branch of every matching git repository to its
+    /// upstream, or to origin setting it as the upstream.
+    pub(super) async fn push(
+        &self,
+        filter: &RepoFilter,
+        force_with_lease: bool,
+    ) -> anyhow::Result<()> {
+        let git = self.git();
+        let mut failed = 0;
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            let params = |key: &str, value: &str| {
+                Some(vec![
+                    ("repo".to_string(), repo.path.to_owned()),
+                    (key.to_string(), value.to_string()),
+                ])
+            };
+            match self.push_repo(&git, &dir, force_with_lease).await {
+                Ok(PushResult::Pushed(branch)) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-pushed",
+                            params("branch", &branch)
+                        )
+                    )
+                ),
+                Ok(PushResult::UpToDate(branch)) => println!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-push-up-to-date",
+                        params("branch", &branch)
+                    )
+                ),
+                Ok(PushResult::Detached) => println!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-push-detached",
+                        Some(vec![("repo".to_string(), repo.path.to_owned())])
+                    )
+                ),
+                Ok(PushResult::Rejected(branch)) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Dirty,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-push-rejected",
+                                params("branch", &branch)
+                            )
+                        )
+                    );
+                }
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-push-failed",
+                                params("error", &err.to_string())
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    async fn push_repo(
+        &self,
+        git: &Git,
+        dir: &Path,
+        force_with_lease: bool,
+    ) -> anyhow::Result<PushResult> {
+        let Ok(branch) = git.current_branch(dir).await else {
+            return Ok(PushResult::Detached);
+        };
+        let upstream = git
+            .output(dir, &["config", &format!("branch.{}.remote", branch)])
+            .await
+            .ok();
+        let remote = upstream.as_deref().map(str::trim).unwrap_or("origin");
+        // decided on what was last fetched, git refuses what changed since
+        let tracking = format!("refs/remotes/{}/{}", remote, branch);
+        if let Ok(pushed) = git
+            .output(dir, &["rev-parse", "--verify", "--quiet", &tracking])
+            .await
+        {
+            if git.head(dir).await.as_deref().map(str::trim) == Some(pushed.trim()) {
+                return Ok(PushResult::UpToDate(branch));
+            }
+            if !force_with_lease
+                && git
+                    .output(dir, &["merge-base", "--is-ancestor", &tracking, "HEAD"])
+                    .await
+                    .is_err()
+            {
+                return Ok(PushResult::Rejected(branch));
+            }
+        }
+        let mut args = vec!["push"];
+        if force_with_lease {
+            args.push("--force-with-lease");
+        }
+        if upstream.is_none() {
+            args.push("--set-upstream");
+        }
+        args.extend([remote, branch.as_str()]);
+        git.output(dir, &args).await?;
+        Ok(PushResult::Pushed(branch))
+    }
+}
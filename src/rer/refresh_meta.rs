@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::{cli::RepoFilter, forge::Forge, git::GitUrl, meta::RepoMeta, style::Paint};
+
+use super::Rer;
+
+impl Rer {
+    /// Stores the description and topics from the forge of `url` in the
+    /// metadata of the repository at `dir`.
+    pub(super) async fn fetch_meta(&self, url: &GitUrl, dir: &Path) -> anyhow::Result<()> {
+        let host = url.host();
+        let project = url.project_path();
+        let info = match self
+            .forge_of_host(host)
+            .map(|name| (name, Forge::from_name(name)))
+        {
+            Some((_, Some(forge))) => forge.repo_info(self.api()?, host, &project).await?,
+            Some((name, None)) => self.repo_info_with_plugin(name, host, &project).await?,
+            None => match Forge::detect(host) {
+                Some(forge) => forge.repo_info(self.api()?, host, &project).await?,
+                None => return Ok(()),
+            },
+        };
+        let mut meta = RepoMeta::load(dir).await?;
+        meta.description = info.description;
+        meta.topics = info.topics;
+        meta.save(dir).await
+    }
+
+    pub(super) async fn refresh_meta(&self, filter: &RepoFilter) -> anyhow::Result<()> {
+        let git = self.git();
+        let mut failed = 0;
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            // repositories without an origin have nothing to fetch from
+            let Ok(url) = git.remote_url(&dir, "origin").await else {
+                continue;
+            };
+            let result = match GitUrl::parse(url) {
+                Ok(url) => self.fetch_meta(&url, &dir).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                failed += 1;
+                eprintln!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Error,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-refresh-meta-failed",
+                            Some(vec![
+                                ("repo".to_string(), repo.path.to_owned()),
+                                ("error".to_string(), err.to_string()),
+                            ])
+                        )
+                    )
+                );
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+}
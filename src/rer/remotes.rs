@@ -0,0 +1,49 @@
+use tabled::Tabled;
+
+use crate::cli::RemotesCommands;
+
+use super::Rer;
+
+#[derive(Tabled)]
+struct RemoteTableItem {
+    path: String,
+    hostname: String,
+    remote: String,
+    url: String,
+}
+
+impl Rer {
+    pub(super) async fn remotes(&self, commands: &RemotesCommands) -> anyhow::Result<()> {
+        let git = self.git();
+        match commands {
+            RemotesCommands::List { filter } => {
+                let mut list = vec![];
+                for repo in self.scan_repos(filter).await? {
+                    if repo.ty != "git" {
+                        continue;
+                    }
+                    for (remote, url) in git.remote_urls(self.dir_of(&repo)?).await? {
+                        list.push(RemoteTableItem {
+                            path: repo.path.to_owned(),
+                            hostname: repo.hostname.to_owned(),
+                            remote,
+                            url,
+                        });
+                    }
+                }
+                println!("{}", self.render_table(tabled::Table::new(list)));
+                Ok(())
+            }
+            RemotesCommands::Add { target, name, url } => {
+                let dir = self.find_repo(target).await?;
+                git.output(&dir, &["remote", "add", name, url]).await?;
+                Ok(())
+            }
+            RemotesCommands::Rm { target, name } => {
+                let dir = self.find_repo(target).await?;
+                git.output(&dir, &["remote", "remove", name]).await?;
+                Ok(())
+            }
+        }
+    }
+}
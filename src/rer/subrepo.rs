@@ -0,0 +1,72 @@
+use std::path::{Component, Path};
+
+use anyhow::anyhow;
+
+use crate::{cli::SubrepoCommands, meta::RepoMeta, style::Paint};
+
+use super::Rer;
+
+impl Rer {
+    pub(super) async fn subrepo(&self, commands: &SubrepoCommands) -> anyhow::Result<()> {
+        match commands {
+            SubrepoCommands::Add { target, path } => self.add_subrepo(target, path).await,
+        }
+    }
+
+    /// Registers the directory `path` of the repository `target` as a subrepo
+    /// in its metadata, so it can be targeted as `<target>/<path>`.
+    async fn add_subrepo(&self, target: &str, path: &str) -> anyhow::Result<()> {
+        let dir = self.find_repo(target).await?;
+        let invalid = || {
+            anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-invalid-subrepo",
+                    Some(vec![
+                        ("path".to_string(), path.to_string()),
+                        ("repo".to_string(), dir.to_string_lossy().to_string()),
+                    ])
+                )
+            )
+        };
+        // kept relative and without `..`, so it stays inside the repository
+        let components = Path::new(path)
+            .components()
+            .map(|component| match component {
+                Component::Normal(name) => Ok(name.to_string_lossy().to_string()),
+                Component::CurDir => Ok(String::new()),
+                _ => Err(invalid()),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let sub = components
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+        if sub.is_empty() || !dir.join(&sub).is_dir() {
+            return Err(invalid());
+        }
+        let mut meta = RepoMeta::load(&dir).await?;
+        if !meta.subrepos.contains(&sub) {
+            meta.subrepos.push(sub.to_owned());
+            meta.subrepos.sort();
+            meta.save(&dir).await?;
+        }
+        println!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "info-subrepo-added",
+                    Some(vec![
+                        ("path".to_string(), sub),
+                        ("repo".to_string(), target.to_string()),
+                    ])
+                )
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::{cli::RepoFilter, git::Git, style::Paint};
+
+use super::Rer;
+
+enum SyncResult {
+    Synced(String),
+    NeedsMerge(String),
+}
+
+impl Rer {
+    pub(super) async fn sync_fork(
+        &self,
+        target: Option<&str>,
+        filter: &RepoFilter,
+    ) -> anyhow::Result<()> {
+        let git = self.git();
+        let repos = match target {
+            Some(target) => vec![(target.to_string(), self.find_repo(target).await?)],
+            None => {
+                let mut repos = vec![];
+                for repo in self.scan_repos(filter).await? {
+                    let dir = self.dir_of(&repo)?;
+                    if repo.ty == "git" && git.remotes(&dir).await?.iter().any(|r| r == "upstream")
+                    {
+                        repos.push((repo.path, dir));
+                    }
+                }
+                repos
+            }
+        };
+        let mut needs_merge = vec![];
+        let mut failed = 0;
+        for (path, dir) in repos {
+            let params = |branch: &str| {
+                Some(vec![
+                    ("repo".to_string(), path.to_owned()),
+                    ("branch".to_string(), branch.to_string()),
+                ])
+            };
+            match self.sync_fork_repo(&git, &dir).await {
+                Ok(SyncResult::Synced(branch)) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-fork-synced",
+                            params(&branch)
+                        )
+                    )
+                ),
+                Ok(SyncResult::NeedsMerge(branch)) => {
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Dirty,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-fork-needs-merge",
+                                params(&branch)
+                            )
+                        )
+                    );
+                    needs_merge.push(path.to_owned());
+                }
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-fork-sync-failed",
+                                Some(vec![
+                                    ("repo".to_string(), path.to_owned()),
+                                    ("error".to_string(), err.to_string()),
+                                ])
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        if !needs_merge.is_empty() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-forks-need-merge",
+                    Some(vec![("repos".to_string(), needs_merge.join(", "))])
+                )
+            ));
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    async fn sync_fork_repo(&self, git: &Git, dir: &Path) -> anyhow::Result<SyncResult> {
+        git.output(dir, &["fetch", "upstream"]).await?;
+        let branch = git.default_branch(dir, "upstream").await?;
+        let upstream_branch = format!("upstream/{}", branch);
+        let fast_forward = if git.current_branch(dir).await.ok().as_ref() == Some(&branch) {
+            git.output(dir, &["merge", "--ff-only", &upstream_branch])
+                .await
+        } else {
+            // updates the branch without checking it out, refusing non fast-forwards
+            git.output(
+                dir,
+                &["fetch", ".", &format!("{}:{}", upstream_branch, branch)],
+            )
+            .await
+        };
+        if fast_forward.is_err() {
+            return Ok(SyncResult::NeedsMerge(branch));
+        }
+        git.output(dir, &["push", "origin", &branch]).await?;
+        Ok(SyncResult::Synced(branch))
+    }
+}
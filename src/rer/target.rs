@@ -0,0 +1,299 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+
+use crate::{cli::RepoFilter, layout, meta::META_FILE};
+
+use super::{RepoTableItem, Rer};
+
+/// A target given on the command line, see [`Rer::resolve_targets`].
+enum Target<'a> {
+    /// `.`, the repository of the working directory.
+    Cwd,
+    /// A directory in or of a repository, absolute or starting with `./`,
+    /// `../` or `~`.
+    Dir(PathBuf),
+    /// `@tag`, the repositories with a topic.
+    Tag(&'a str),
+    /// `%group`, the repositories of a group.
+    Group(&'a str),
+    /// `host/owner/name`, `owner/name` or `name`.
+    Repo(&'a str),
+}
+
+impl<'a> Target<'a> {
+    fn parse(target: &'a str) -> Self {
+        if target == "." {
+            Target::Cwd
+        } else if let Some(tag) = target.strip_prefix('@') {
+            Target::Tag(tag)
+        } else if let Some(group) = target.strip_prefix('%') {
+            Target::Group(group)
+        } else if target == ".."
+            || ["/", "./", "../", "~"]
+                .iter()
+                .any(|prefix| target.starts_with(prefix))
+        {
+            Target::Dir(layout::expand_home(target))
+        } else {
+            Target::Repo(target)
+        }
+    }
+}
+
+/// Splits the host off a target like `github.com/owner/name` naming a new
+/// repository, the first of at least three segments if it has a dot.
+pub(super) fn split_host(target: &str) -> (Option<&str>, &str) {
+    match target.split_once('/') {
+        Some((host, rest)) if host.contains('.') && rest.contains('/') => (Some(host), rest),
+        _ => (None, target),
+    }
+}
+
+impl Rer {
+    /// The repositories `target` stands for: `.` for the working directory, a
+    /// directory, `@tag` for the repositories with a topic, `%group` for a
+    /// group, or `host/owner/name`, `owner/name` or just `name`, tried in this
+    /// order until one matches.
+    pub(super) async fn resolve_targets(&self, target: &str) -> anyhow::Result<Vec<RepoTableItem>> {
+        let parsed = Target::parse(target);
+        let suggest = matches!(parsed, Target::Repo(_));
+        // directories are looked up on their own
+        let all = match parsed {
+            Target::Tag(_) | Target::Group(_) | Target::Repo(_) => {
+                self.scan_repos(&RepoFilter::default()).await?
+            }
+            _ => vec![],
+        };
+        let repos = self.match_target(&parsed, &all).await?;
+        if repos.is_empty() && suggest {
+            return Err(self.target_not_found(target).await);
+        }
+        if repos.is_empty() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n
+                    .format_msg_or_log(&self.lang_id, "error-target-not-found", None)
+            ));
+        }
+        Ok(repos)
+    }
+
+    /// The repositories of `repos` a parsed target stands for.
+    async fn match_target(
+        &self,
+        target: &Target<'_>,
+        repos: &[RepoTableItem],
+    ) -> anyhow::Result<Vec<RepoTableItem>> {
+        Ok(match target {
+            Target::Cwd => self
+                .repo_containing(&std::env::current_dir()?)
+                .await?
+                .into_iter()
+                .collect(),
+            Target::Dir(dir) => self.repo_containing(dir).await?.into_iter().collect(),
+            Target::Tag(tag) => repos
+                .iter()
+                .filter(|repo| repo.topics.iter().any(|topic| topic == tag))
+                .cloned()
+                .collect(),
+            Target::Group(group) => Box::pin(self.group_members(group, repos)).await?,
+            Target::Repo(spec) => {
+                let matchers: [fn(&RepoTableItem, &str) -> bool; 3] = [
+                    |repo, spec| format!("{}/{}", repo.hostname, repo.path) == spec,
+                    |repo, spec| repo.path == spec,
+                    |repo, spec| layout::split_path(&repo.path).1 == spec,
+                ];
+                // subrepos are targets like those of their repository with
+                // their path appended
+                matchers
+                    .iter()
+                    .map(|matches| {
+                        repos
+                            .iter()
+                            .filter(|repo| matches(repo, spec))
+                            .cloned()
+                            .collect::<Vec<_>>()
+                    })
+                    .chain(matchers.iter().map(|matches| {
+                        repos
+                            .iter()
+                            .flat_map(|repo| {
+                                repo.subrepos
+                                    .iter()
+                                    .filter(|sub| {
+                                        spec.strip_suffix(sub.as_str())
+                                            .and_then(|spec| spec.strip_suffix('/'))
+                                            .is_some_and(|spec| matches(repo, spec))
+                                    })
+                                    .map(|sub| repo.subrepo(sub))
+                            })
+                            .collect::<Vec<_>>()
+                    }))
+                    .find(|repos| !repos.is_empty())
+                    .unwrap_or_default()
+            }
+        })
+    }
+
+    /// The repositories of `repos` in the configured group `name`: those its
+    /// targets stand for, and those matching its filters if it has any.
+    pub(super) async fn group_members(
+        &self,
+        name: &str,
+        repos: &[RepoTableItem],
+    ) -> anyhow::Result<Vec<RepoTableItem>> {
+        let Some(group) = self
+            .config
+            .groups
+            .as_ref()
+            .and_then(|groups| groups.get(name))
+        else {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-unknown-group",
+                    Some(vec![("group".to_string(), name.to_string())])
+                )
+            ));
+        };
+        let mut members = vec![];
+        for target in &group.targets {
+            let parsed = Target::parse(target);
+            if matches!(parsed, Target::Group(_)) {
+                return Err(anyhow!(
+                    "{}",
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "error-nested-group",
+                        Some(vec![
+                            ("group".to_string(), name.to_string()),
+                            ("target".to_string(), target.to_string()),
+                        ])
+                    )
+                ));
+            }
+            members.extend(self.match_target(&parsed, repos).await?);
+        }
+        let filter = RepoFilter {
+            filter_type: group.ty.clone(),
+            filter_hostname: group.hostname.clone(),
+            filter_owner: group.owner.clone(),
+            filter_user: group.user.clone(),
+            filter_path: group.path.clone(),
+            filter_devcontainer: group.devcontainer.unwrap_or_default(),
+            filter_lang: group.lang.clone(),
+            filter_script: group.script.clone(),
+            filter_where: group.filter_where.clone(),
+            group: None,
+        };
+        if filter.filter_type.is_some()
+            || filter.filter_hostname.is_some()
+            || filter.filter_owner.is_some()
+            || filter.filter_user.is_some()
+            || filter.filter_path.is_some()
+            || filter.filter_devcontainer
+            || filter.filter_lang.is_some()
+            || filter.filter_script.is_some()
+            || filter.filter_where.is_some()
+        {
+            members.extend(Box::pin(self.filter_repos(repos.to_vec(), &filter)).await?);
+        }
+        let mut seen = HashSet::new();
+        members.retain(|repo| {
+            seen.insert((repo.ty.clone(), repo.hostname.clone(), repo.path.clone()))
+        });
+        Ok(members)
+    }
+
+    /// Finds the directory of the one repository `target` stands for, see
+    /// [`Rer::resolve_targets`].
+    pub(super) async fn find_repo(&self, target: &str) -> anyhow::Result<PathBuf> {
+        self.dir_of(&self.find_repo_item(target).await?)
+    }
+
+    /// The one repository `target` stands for.
+    pub(super) async fn find_repo_item(&self, target: &str) -> anyhow::Result<RepoTableItem> {
+        let mut repos = self.resolve_targets(target).await?;
+        match repos.as_slice() {
+            [_] => Ok(repos.remove(0)),
+            repos => Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-target-ambiguous",
+                    Some(vec![
+                        ("target".to_string(), target.to_string()),
+                        (
+                            "candidates".to_string(),
+                            repos
+                                .iter()
+                                .map(|repo| format!("{}/{}", repo.hostname, repo.path))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    ])
+                )
+            )),
+        }
+    }
+
+    /// The repository `dir` is in.
+    async fn repo_containing(&self, dir: &Path) -> anyhow::Result<Option<RepoTableItem>> {
+        let dir = std::path::absolute(dir)?;
+        for ancestor in dir.ancestors() {
+            if ancestor.join(".git").exists() || ancestor.join(META_FILE).exists() {
+                return self.repo_at(ancestor).await;
+            }
+        }
+        Ok(None)
+    }
+
+    /// The error for a missing target, suggesting the closest repositories.
+    async fn target_not_found(&self, target: &str) -> anyhow::Error {
+        // the index, which the lookup just saved, spares walking the roots
+        let mut candidates = self
+            .indexed_repos()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|repo| {
+                let name = repo.path.rsplit('/').next().unwrap_or(&repo.path);
+                let distance = if repo.path.contains(target) {
+                    0
+                } else {
+                    strsim::levenshtein(target, &repo.path).min(strsim::levenshtein(target, name))
+                };
+                (distance <= (target.len() / 3).max(2)).then_some((distance, repo.path))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort();
+        if candidates.is_empty() {
+            return anyhow!(
+                "{}",
+                self.i18n
+                    .format_msg_or_log(&self.lang_id, "error-target-not-found", None)
+            );
+        }
+        anyhow!(
+            "{}",
+            self.i18n.format_msg_or_log(
+                &self.lang_id,
+                "error-target-not-found-suggest",
+                Some(vec![(
+                    "candidates".to_string(),
+                    candidates
+                        .into_iter()
+                        .take(3)
+                        .map(|(_, path)| path)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )])
+            )
+        )
+    }
+}
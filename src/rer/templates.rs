@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+
+use crate::{
+    cli::TemplateCommands, fs::PartialDir, git::GitUrl, layout,
+    meta::filter_repo_paths_recursively, style::Paint,
+};
+
+use super::Rer;
+
+const TEMPLATES_DIR: &str = "templates";
+
+/// Hosts of the prefixes of templates like `gh:owner/name`.
+const TEMPLATE_HOSTS: [(&str, &str); 2] = [("gh:", "github.com"), ("gl:", "gitlab.com")];
+
+impl Rer {
+    pub(super) async fn templates(&self, commands: &TemplateCommands) -> anyhow::Result<()> {
+        match commands {
+            TemplateCommands::Update { template } => {
+                self.update_templates(template.as_deref()).await
+            }
+        }
+    }
+
+    /// The git url of `template` if it is kept in a repository, given as
+    /// `url` in the config or as the template itself.
+    fn template_url(&self, template: &str) -> anyhow::Result<Option<(GitUrl, String)>> {
+        let configured = self
+            .config
+            .templates
+            .as_ref()
+            .and_then(|templates| templates.get(template));
+        if let Some(configured) = configured {
+            return match configured.url.as_deref() {
+                Some(url) => Ok(Some(self.resolve_template_url(url)?)),
+                None => Ok(None),
+            };
+        }
+        let remote = template.contains("://")
+            || TEMPLATE_HOSTS
+                .iter()
+                .any(|(prefix, _)| template.starts_with(prefix))
+            || GitUrl::parse_shorthand(template).is_some()
+            || lazy_regex::regex_is_match!(r"^[\w.-]+@[\w.-]+:", template);
+        match remote {
+            true => Ok(Some(self.resolve_template_url(template)?)),
+            false => Ok(None),
+        }
+    }
+
+    fn resolve_template_url(&self, url: &str) -> anyhow::Result<(GitUrl, String)> {
+        let expanded = TEMPLATE_HOSTS.iter().find_map(|(prefix, host)| {
+            url.strip_prefix(prefix)
+                .map(|path| format!("{}/{}", host, path))
+        });
+        self.resolve_clone_target(expanded.as_deref().unwrap_or(url))
+    }
+
+    /// Where the clone of the template at `url` is kept.
+    fn template_cache(&self, url: &GitUrl) -> anyhow::Result<PathBuf> {
+        let (host, owner, name) = url.canonical();
+        Ok(self
+            .data_dir()?
+            .join(TEMPLATES_DIR)
+            .join(host)
+            .join(owner)
+            .join(name))
+    }
+
+    /// The directory of the project template `template`, cloning it first if
+    /// it is kept in a repository that was not used before.
+    pub(super) async fn template_dir(&self, template: &str) -> anyhow::Result<PathBuf> {
+        if let Some((url, target)) = self.template_url(template)? {
+            let dir = self.template_cache(&url)?;
+            if !dir.exists() {
+                let partial = PartialDir::new(&dir);
+                let (status, stderr) = self
+                    .git()
+                    .run_captured(&["clone", "--quiet", "--", &target, &dir.to_string_lossy()])
+                    .await?;
+                if !status.success() {
+                    return Err(anyhow!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-template-clone-failed",
+                            Some(vec![
+                                ("url".to_string(), target),
+                                ("error".to_string(), stderr.trim().to_string()),
+                            ])
+                        )
+                    ));
+                }
+                partial.keep();
+            }
+            return Ok(dir);
+        }
+        let configured = self
+            .config
+            .templates
+            .as_ref()
+            .and_then(|templates| templates.get(template))
+            .and_then(|template| template.path.as_deref());
+        let dir = match configured {
+            Some(path) => layout::expand_home(path),
+            None => self
+                .config_file()?
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join(TEMPLATES_DIR)
+                .join(template),
+        };
+        if !dir.is_dir() {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-unknown-template",
+                    Some(vec![
+                        ("template".to_string(), template.to_string()),
+                        ("dir".to_string(), dir.to_string_lossy().to_string()),
+                    ])
+                )
+            ));
+        }
+        Ok(dir)
+    }
+
+    /// What the tool of an external template is given for `template`: its
+    /// directory, or its url unless the tool is to resolve that itself.
+    pub(super) async fn external_template_source(&self, template: &str) -> anyhow::Result<String> {
+        let url = self
+            .config
+            .templates
+            .as_ref()
+            .and_then(|templates| templates.get(template))
+            .and_then(|template| template.url.to_owned());
+        match (url, self.template_url(template)?) {
+            (Some(url), _) => Ok(url),
+            // both tools know urls and the gh: and gl: prefixes
+            (None, Some(_)) => Ok(template.to_string()),
+            (None, None) => Ok(std::path::absolute(self.template_dir(template).await?)?
+                .to_string_lossy()
+                .to_string()),
+        }
+    }
+
+    /// Pulls the clone of the remote template `template`, or of all the
+    /// templates that were cloned.
+    async fn update_templates(&self, template: Option<&str>) -> anyhow::Result<()> {
+        let dirs = match template {
+            Some(template) => {
+                let Some((url, _)) = self.template_url(template)? else {
+                    return Err(anyhow!(
+                        "{}",
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "error-template-not-remote",
+                            Some(vec![("template".to_string(), template.to_string())])
+                        )
+                    ));
+                };
+                let dir = self.template_cache(&url)?;
+                if !dir.exists() {
+                    // cloning is all there is to update
+                    self.template_dir(template).await?;
+                }
+                vec![dir]
+            }
+            None => {
+                let cache = self.data_dir()?.join(TEMPLATES_DIR);
+                if !cache.exists() {
+                    return Ok(());
+                }
+                filter_repo_paths_recursively(&cache, ".git", &mut vec![]).await?
+            }
+        };
+        let cache = self.data_dir()?.join(TEMPLATES_DIR);
+        let git = self.git();
+        let mut failed = 0;
+        for dir in dirs {
+            let name = dir
+                .strip_prefix(&cache)
+                .unwrap_or(&dir)
+                .to_string_lossy()
+                .to_string();
+            match git.output(&dir, &["pull", "--quiet", "--ff-only"]).await {
+                Ok(_) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-template-updated",
+                            Some(vec![("template".to_string(), name)])
+                        )
+                    )
+                ),
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-template-update-failed",
+                                Some(vec![
+                                    ("template".to_string(), name),
+                                    ("error".to_string(), err.to_string()),
+                                ])
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-templates-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use tabled::Tabled;
+
+use crate::cli::RepoFilter;
+
+use super::Rer;
+
+#[derive(Tabled, serde::Serialize)]
+struct TimelineItem {
+    path: String,
+    hostname: String,
+    #[tabled(skip)]
+    #[serde(skip)]
+    timestamp: i64,
+    time: String,
+    author: String,
+    #[tabled(skip)]
+    commit: String,
+    subject: String,
+}
+
+impl Rer {
+    /// Prints the commits of all matching repositories since `since`, newest
+    /// first.
+    pub(super) async fn timeline(
+        &self,
+        since: &str,
+        author: Option<&str>,
+        json: bool,
+        filter: &RepoFilter,
+    ) -> anyhow::Result<()> {
+        let git = self.git();
+        let since = format!("--since={}", since);
+        let author = author.map(|author| format!("--author={}", author));
+        let mut list = vec![];
+        for repo in self.scan_repos(filter).await? {
+            if repo.ty != "git" {
+                continue;
+            }
+            let dir = self.dir_of(&repo)?;
+            if git
+                .output(&dir, &["rev-parse", "--verify", "--quiet", "HEAD"])
+                .await
+                .is_err()
+            {
+                continue;
+            }
+            let mut args = vec!["log", "--format=%at%x1f%aI%x1f%an%x1f%H%x1f%s", &since];
+            if let Some(ref author) = author {
+                args.push(author);
+            }
+            for line in git.output(&dir, &args).await?.lines() {
+                let fields = line.splitn(5, '\x1f').collect::<Vec<_>>();
+                let [timestamp, time, author, commit, subject] = fields[..] else {
+                    continue;
+                };
+                list.push(TimelineItem {
+                    path: repo.path.to_owned(),
+                    hostname: repo.hostname.to_owned(),
+                    timestamp: timestamp.parse().unwrap_or_default(),
+                    time: time.to_string(),
+                    author: author.to_string(),
+                    commit: commit.to_string(),
+                    subject: subject.to_string(),
+                });
+            }
+        }
+        list.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+        if json {
+            println!("{}", serde_json::to_string(&list)?);
+        } else {
+            println!("{}", self.render_table(tabled::Table::new(list)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::anyhow;
+use dialoguer::Confirm;
+
+use crate::{
+    audit::{self, AuditEvent},
+    cli::RepoFilter,
+    fs,
+    git::{self, GitUrl},
+    meta::RepoMeta,
+    outcome::Outcome,
+    porcelain::{self, PorcelainVersion},
+    style::Paint,
+    vendor,
+};
+
+use super::{RepoTableItem, Rer};
+
+impl Rer {
+    /// Updates the matching repositories, printing an [`Outcome`] for each as
+    /// a json array instead of a line with `json`, or a porcelain record with
+    /// `porcelain`. Repositories that moved upstream are followed with
+    /// `relocate`, otherwise after asking.
+    pub(super) async fn update(
+        &self,
+        filter: &RepoFilter,
+        json: bool,
+        relocate: bool,
+        porcelain: Option<PorcelainVersion>,
+    ) -> anyhow::Result<()> {
+        let quiet = json || porcelain.is_some();
+        let mut failed = 0;
+        let mut outcomes = vec![];
+        for repo in self.scan_repos(filter).await? {
+            let params = |error: Option<String>| {
+                let mut params = vec![("repo".to_string(), repo.path.to_owned())];
+                params.extend(error.map(|error| ("error".to_string(), error)));
+                Some(params)
+            };
+            let start = Instant::now();
+            let result = self.update_repo(&repo, quiet, relocate).await;
+            if let Some(PorcelainVersion::V1) = porcelain {
+                let status = match &result {
+                    Ok(updated) if updated.skipped => "skipped",
+                    Ok(updated) if updated.changed => "updated",
+                    Ok(_) => "up-to-date",
+                    Err(_) => "failed",
+                };
+                println!(
+                    "{}",
+                    porcelain::record([
+                        repo.ty.as_str(),
+                        repo.hostname.as_str(),
+                        repo.path.as_str(),
+                        status
+                    ])
+                );
+                if let Err(err) = result {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-repo-update-failed",
+                                params(Some(err.to_string()))
+                            )
+                        )
+                    );
+                }
+                continue;
+            }
+            if json {
+                let outcome = Outcome::new(self.dir_of(&repo)?, start);
+                outcomes.push(match result {
+                    Ok(updated) => Outcome {
+                        path: updated
+                            .relocated
+                            .map(|dir| dir.to_string_lossy().to_string())
+                            .unwrap_or(outcome.path),
+                        updated: Some(updated.changed),
+                        skipped: updated.skipped.then_some(true),
+                        moved_to: updated.moved_to,
+                        ..outcome
+                    },
+                    Err(err) => {
+                        failed += 1;
+                        outcome.with_error(&err)
+                    }
+                });
+                continue;
+            }
+            match result {
+                Ok(updated) => println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            if updated.skipped {
+                                "info-repo-no-upstream"
+                            } else if updated.changed {
+                                "info-repo-updated"
+                            } else {
+                                "info-repo-up-to-date"
+                            },
+                            params(None)
+                        )
+                    )
+                ),
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}",
+                        self.painter.paint(
+                            Paint::Error,
+                            self.i18n.format_msg_or_log(
+                                &self.lang_id,
+                                "error-repo-update-failed",
+                                params(Some(err.to_string()))
+                            )
+                        )
+                    );
+                }
+            }
+        }
+        if json {
+            println!("{}", serde_json::to_string(&outcomes)?);
+        }
+        if failed > 0 {
+            return Err(anyhow!(
+                "{}",
+                self.i18n.format_msg_or_log(
+                    &self.lang_id,
+                    "error-some-repos-failed",
+                    Some(vec![("count".to_string(), failed.to_string())])
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Updates one repository, following it when it moved upstream. Nothing is
+    /// printed or asked with `quiet`.
+    async fn update_repo(
+        &self,
+        repo: &RepoTableItem,
+        quiet: bool,
+        relocate: bool,
+    ) -> anyhow::Result<RepoUpdate> {
+        let dir = self.dir_of(repo)?;
+        match repo.ty.as_str() {
+            "git" => {
+                let git = self.git();
+                // like repositories made with create, which have nothing to pull
+                if git
+                    .output(&dir, &["rev-parse", "--abbrev-ref", "@{u}"])
+                    .await
+                    .is_err()
+                {
+                    return Ok(RepoUpdate {
+                        skipped: true,
+                        ..RepoUpdate::default()
+                    });
+                }
+                let before = git.head(&dir).await;
+                let (_, stderr) = git.output_with_stderr(&dir, &["pull", "--ff-only"]).await?;
+                let changed = git.head(&dir).await != before;
+                let moved_to = git::moved_url(&stderr);
+                let relocated = match moved_to {
+                    Some(ref url) => self.follow_move(repo, &dir, url, quiet, relocate).await?,
+                    None => None,
+                };
+                Ok(RepoUpdate {
+                    changed,
+                    moved_to,
+                    relocated,
+                    ..RepoUpdate::default()
+                })
+            }
+            "vendor" => {
+                let url = RepoMeta::load(&dir)
+                    .await?
+                    .vendor
+                    .ok_or_else(|| anyhow!("missing vendor metadata"))?
+                    .url;
+                Ok(RepoUpdate {
+                    changed: vendor::fetch(&url, &dir).await?,
+                    ..RepoUpdate::default()
+                })
+            }
+            ty => Ok(RepoUpdate {
+                changed: self.update_with_plugin(ty, &dir).await?,
+                ..RepoUpdate::default()
+            }),
+        }
+    }
+
+    /// Moves the repository in `dir` to the directory of `url` and rewrites
+    /// its origin to `url`, recording it in the audit log. Asks first unless
+    /// `relocate`, and with `quiet` only follows with `relocate`. Returns the
+    /// new directory if it was followed.
+    async fn follow_move(
+        &self,
+        repo: &RepoTableItem,
+        dir: &Path,
+        url: &str,
+        quiet: bool,
+        relocate: bool,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let params = |key: &str, value: String| {
+            Some(vec![
+                ("repo".to_string(), repo.path.to_owned()),
+                (key.to_string(), value),
+            ])
+        };
+        let follow = relocate
+            || (!quiet
+                && std::io::stdin().is_terminal()
+                && Confirm::new()
+                    .with_prompt(self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "prompt-follow-move",
+                        params("url", url.to_owned()),
+                    ))
+                    .default(true)
+                    .interact()?);
+        if !follow {
+            if !quiet {
+                println!(
+                    "{}",
+                    self.painter.paint(
+                        Paint::Info,
+                        self.i18n.format_msg_or_log(
+                            &self.lang_id,
+                            "info-repo-moved-upstream",
+                            params("url", url.to_owned())
+                        )
+                    )
+                );
+            }
+            return Ok(None);
+        }
+
+        let git = self.git();
+        let from_url = git
+            .output(dir, &["remote", "get-url", "origin"])
+            .await?
+            .trim()
+            .to_string();
+        let new_dir = self.path_of_git_url(&GitUrl::parse(url)?)?;
+        // moved first, so that a failed move leaves origin as it was
+        if new_dir != dir {
+            fs::move_dir(dir, &new_dir).await?;
+        }
+        if let Err(err) = git
+            .output(&new_dir, &["remote", "set-url", "origin", url])
+            .await
+        {
+            if new_dir != dir {
+                fs::move_dir(&new_dir, dir).await?;
+            }
+            return Err(err);
+        }
+        audit::record(
+            &self.audit_file()?,
+            &AuditEvent::Rename {
+                from_url,
+                to_url: url.to_owned(),
+                from_dir: dir.to_string_lossy().to_string(),
+                to_dir: new_dir.to_string_lossy().to_string(),
+            },
+        )
+        .await?;
+        if !quiet {
+            println!(
+                "{}",
+                self.painter.paint(
+                    Paint::Info,
+                    self.i18n.format_msg_or_log(
+                        &self.lang_id,
+                        "info-repo-relocated",
+                        params("dir", new_dir.to_string_lossy().to_string())
+                    )
+                )
+            );
+        }
+        Ok(Some(new_dir))
+    }
+}
+
+#[derive(Default)]
+struct RepoUpdate {
+    changed: bool,
+    /// Whether the repository was left alone, having no upstream to pull.
+    skipped: bool,
+    /// The new url of a repository that moved upstream.
+    moved_to: Option<String>,
+    /// The new directory of a repository that was followed there.
+    relocated: Option<PathBuf>,
+}
@@ -0,0 +1,70 @@
+use std::{collections::BTreeSet, path::PathBuf, time::Duration};
+
+use log::{debug, warn};
+use notify::{RecursiveMode, Watcher};
+
+use crate::style::Paint;
+
+use super::Rer;
+
+/// How long changes are collected before the index is updated, so a clone
+/// or a checkout updates it once.
+const SETTLE: Duration = Duration::from_millis(500);
+
+impl Rer {
+    /// Keeps the index up to date until interrupted, watching the directories
+    /// it was built from for repositories appearing, disappearing, changing
+    /// their metadata or checking out another branch.
+    pub(super) async fn watch(&self) -> anyhow::Result<()> {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let _ = sender.send(event);
+            })?;
+        let mut watched = BTreeSet::new();
+        let mut dirs = self.reindex().await?;
+        println!(
+            "{}",
+            self.painter.paint(
+                Paint::Info,
+                self.i18n
+                    .format_msg_or_log(&self.lang_id, "info-watching", None)
+            )
+        );
+        loop {
+            // only the entries of each directory matter, so none is watched
+            // recursively, which would also watch the working trees
+            let wanted = dirs
+                .into_iter()
+                .filter(|dir| dir.is_dir())
+                .collect::<BTreeSet<_>>();
+            for dir in watched.difference(&wanted) {
+                let _ = watcher.unwatch(dir);
+            }
+            for dir in wanted.difference(&watched) {
+                if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                    debug!("could not watch {}: {:?}", dir.display(), err);
+                }
+            }
+            watched = wanted;
+
+            let Some(event) = receiver.recv().await else {
+                return Ok(());
+            };
+            let mut changed = vec![];
+            collect_changes(event, &mut changed);
+            while let Ok(Some(event)) = tokio::time::timeout(SETTLE, receiver.recv()).await {
+                collect_changes(event, &mut changed);
+            }
+            dirs = self.update_index(&changed).await?;
+        }
+    }
+}
+
+fn collect_changes(event: notify::Result<notify::Event>, changed: &mut Vec<PathBuf>) {
+    match event {
+        Ok(event) if !event.kind.is_access() => changed.extend(event.paths),
+        Ok(_) => {}
+        Err(err) => warn!("watching the repositories failed: {:?}", err),
+    }
+}
@@ -0,0 +1,180 @@
+//! Project templates for `create --template`, directories whose files are
+//! copied into new repositories with `{{ variable }}` in their contents and
+//! names replaced. References to undeclared variables are kept as they are,
+//! so files like CI workflows with their own `{{ }}` survive. Variables are
+//! declared in the `template.toml` of the template, which is not copied.
+//! Templates of cookiecutter and copier are rendered by those tools.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use lazy_regex::regex;
+
+pub const SPEC_FILE: &str = "template.toml";
+
+/// The `template.toml` of a template.
+#[derive(serde::Deserialize, Default)]
+pub struct TemplateSpec {
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+}
+
+/// A value asked for when the template is used, unless given with `--var`.
+#[derive(serde::Deserialize)]
+pub struct Variable {
+    pub name: String,
+    /// Question shown when asking for the value, the name by default.
+    pub prompt: Option<String>,
+    /// Value used when none is given, which may refer to other variables.
+    pub default: Option<String>,
+    /// Regular expression that values have to match.
+    pub pattern: Option<String>,
+}
+
+/// What `create --template` fills a new repository with.
+pub enum Scaffold {
+    /// A template of rerman with the values of its variables.
+    Template(PathBuf, BTreeMap<String, String>),
+    /// A template of another tool, which asks for the values not given.
+    External(Tool, String, BTreeMap<String, String>),
+}
+
+/// Another templating tool, used for templates like `copier:gh:owner/name`.
+#[derive(Clone, Copy)]
+pub enum Tool {
+    Cookiecutter,
+    Copier,
+}
+
+impl Tool {
+    /// The tool of `template` with the template it is given.
+    pub fn of(template: &str) -> Option<(Self, &str)> {
+        if let Some(template) = template.strip_prefix("cookiecutter:") {
+            return Some((Tool::Cookiecutter, template));
+        }
+        template
+            .strip_prefix("copier:")
+            .map(|template| (Tool::Copier, template))
+    }
+
+    pub fn exe(&self) -> &'static str {
+        match self {
+            Tool::Cookiecutter => "cookiecutter",
+            Tool::Copier => "copier",
+        }
+    }
+
+    /// The arguments rendering `source` with `vars` into `to`, or into a
+    /// directory of `output` for tools that name it themselves.
+    pub fn args(
+        &self,
+        source: &str,
+        vars: &BTreeMap<String, String>,
+        to: &Path,
+        output: &Path,
+        interactive: bool,
+    ) -> Vec<String> {
+        let mut args = vec![];
+        match self {
+            Tool::Cookiecutter => {
+                if !interactive {
+                    args.push("--no-input".to_string());
+                }
+                args.extend([
+                    "--output-dir".to_string(),
+                    output.to_string_lossy().to_string(),
+                    source.to_string(),
+                ]);
+                args.extend(vars.iter().map(|(key, value)| format!("{}={}", key, value)));
+            }
+            Tool::Copier => {
+                args.push("copy".to_string());
+                if !interactive {
+                    args.push("--defaults".to_string());
+                }
+                for (key, value) in vars {
+                    args.extend(["--data".to_string(), format!("{}={}", key, value)]);
+                }
+                args.extend([source.to_string(), to.to_string_lossy().to_string()]);
+            }
+        }
+        args
+    }
+}
+
+impl TemplateSpec {
+    pub async fn load(dir: &Path) -> anyhow::Result<Self> {
+        match tokio::fs::read_to_string(dir.join(SPEC_FILE)).await {
+            Ok(spec) => Ok(toml::from_str(&spec)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Replaces the variables of `vars` referenced in `text`.
+pub fn render(text: &str, vars: &BTreeMap<String, String>) -> String {
+    regex!(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.]*)\s*\}\}")
+        .replace_all(text, |captures: &lazy_regex::Captures| {
+            vars.get(&captures[1])
+                .cloned()
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+/// Copies the template in `from` to `to`, rendering the names of all entries
+/// and the contents of text files. The spec and `.git` are not copied.
+pub fn render_dir(from: &Path, to: &Path, vars: &BTreeMap<String, String>) -> anyhow::Result<()> {
+    render_entries(&from.join(SPEC_FILE), from, to, vars)
+}
+
+fn render_entries(
+    spec: &Path,
+    from: &Path,
+    to: &Path,
+    vars: &BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == ".git" || entry.path() == spec {
+            continue;
+        }
+        let dest = to.join(render(&name.to_string_lossy(), vars));
+        if entry.file_type()?.is_dir() {
+            render_entries(spec, &entry.path(), &dest, vars)?;
+            continue;
+        }
+        let content = std::fs::read(entry.path())?;
+        match String::from_utf8(content) {
+            Ok(text) => std::fs::write(&dest, render(&text, vars))?,
+            // binary files are copied as they are
+            Err(err) => std::fs::write(&dest, err.into_bytes())?,
+        }
+        #[cfg(unix)]
+        std::fs::set_permissions(&dest, entry.metadata()?.permissions())?;
+    }
+    Ok(())
+}
+
+/// Moves what a tool generated in `output` to `to`, the contents of the
+/// directory it created if it created just one.
+pub fn move_generated(output: &Path, to: &Path) -> anyhow::Result<()> {
+    let entries = std::fs::read_dir(output)?.collect::<Result<Vec<_>, _>>()?;
+    let from = match entries.as_slice() {
+        [entry] if entry.file_type()?.is_dir() => entry.path(),
+        _ => output.to_path_buf(),
+    };
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        std::fs::rename(entry.path(), to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
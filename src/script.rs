@@ -0,0 +1,75 @@
+//! Rhai scripts for hooks and filters. A script sees the repository as the
+//! map `repo`, with the fields of `list --json`, missing ones being `()`, and
+//! its directory as `dir`, which is also `repo.dir`. `run(command)` runs a
+//! shell command in that directory and returns its exit code.
+
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::anyhow;
+use rhai::{Dynamic, Engine, Scope, AST};
+
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    dir: Rc<RefCell<PathBuf>>,
+}
+
+impl Script {
+    pub fn load(file: &Path) -> anyhow::Result<Self> {
+        let dir = Rc::new(RefCell::new(PathBuf::new()));
+        let mut engine = Engine::new();
+        let run_dir = dir.clone();
+        engine.register_fn("run", move |command: &str| -> i64 {
+            let mut child = if cfg!(target_os = "windows") {
+                let mut child = std::process::Command::new("cmd");
+                child.arg("/C");
+                child
+            } else {
+                let mut child = std::process::Command::new("sh");
+                child.arg("-c");
+                child
+            };
+            match child.arg(command).current_dir(&*run_dir.borrow()).status() {
+                Ok(status) => status.code().unwrap_or(-1).into(),
+                Err(_) => -1,
+            }
+        });
+        let ast = engine
+            .compile_file(file.to_path_buf())
+            .map_err(|err| anyhow!("{}: {}", file.display(), err))?;
+        Ok(Self { engine, ast, dir })
+    }
+
+    /// Runs the script for `repo` in `dir`, returning its last value.
+    pub fn eval(&self, repo: &impl serde::Serialize, dir: &Path) -> anyhow::Result<Dynamic> {
+        let mut map = rhai::serde::to_dynamic(repo)
+            .map_err(|err| anyhow!("{}", err))?
+            .try_cast::<rhai::Map>()
+            .ok_or_else(|| anyhow!("repository is not an object"))?;
+        map.insert("dir".into(), dir.to_string_lossy().to_string().into());
+        *self.dir.borrow_mut() = dir.to_path_buf();
+        let mut scope = Scope::new();
+        scope.push("repo", map);
+        scope.push("dir", dir.to_string_lossy().to_string());
+        self.engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|err| anyhow!("{}", err))
+    }
+
+    /// Runs the script as a hook for `repo`, ignoring its value.
+    pub fn run(&self, repo: &impl serde::Serialize, dir: &Path) -> anyhow::Result<()> {
+        self.eval(repo, dir).map(drop)
+    }
+
+    /// Whether the script, used as a filter, keeps `repo`.
+    pub fn matches(&self, repo: &impl serde::Serialize, dir: &Path) -> anyhow::Result<bool> {
+        let value = self.eval(repo, dir)?;
+        value
+            .as_bool()
+            .map_err(|ty| anyhow!("filter script returned {} instead of a bool", ty))
+    }
+}
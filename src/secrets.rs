@@ -0,0 +1,46 @@
+//! A built-in set of patterns of secrets, for `audit secrets` when gitleaks
+//! is not installed.
+
+use lazy_regex::{Lazy, Regex};
+
+/// Patterns of well known token formats and of assignments to variables
+/// named like secrets, by the rule they are reported as.
+const RULES: &[(&str, &str)] = &[
+    ("aws-access-key-id", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("github-token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("github-token", r"\bgithub_pat_[A-Za-z0-9_]{22,}"),
+    ("gitlab-token", r"\bglpat-[A-Za-z0-9_-]{20,}"),
+    ("slack-token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    ("google-api-key", r"\bAIza[0-9A-Za-z_-]{35}\b"),
+    ("stripe-key", r"\b(?:sk|rk)_live_[0-9A-Za-z]{24,}\b"),
+    (
+        "private-key",
+        r"-----BEGIN (?:RSA |EC |DSA |OPENSSH |PGP |ENCRYPTED )?PRIVATE KEY(?: BLOCK)?-----",
+    ),
+    (
+        "generic-secret",
+        r#"(?i)\b(?:password|passwd|secret|api_?key|access_?token|auth_?token)\b["']?\s*[:=]\s*["'][^"'\s]{8,}["']"#,
+    ),
+];
+
+static PATTERNS: Lazy<Vec<(&str, Regex)>> = Lazy::new(|| {
+    RULES
+        .iter()
+        .map(|(rule, pattern)| (*rule, Regex::new(pattern).expect("valid secret pattern")))
+        .collect()
+});
+
+/// The rules matching on each line of `text`, with the line numbers.
+pub fn scan(text: &str) -> Vec<(usize, &'static str)> {
+    let mut found = vec![];
+    for (index, line) in text.lines().enumerate() {
+        for (rule, pattern) in PATTERNS.iter() {
+            if pattern.is_match(line) {
+                found.push((index + 1, *rule));
+                // one finding per line, the first rule being the most specific
+                break;
+            }
+        }
+    }
+    found
+}
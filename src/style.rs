@@ -0,0 +1,116 @@
+use std::{fmt::Display, io::IsTerminal};
+
+use log::warn;
+use owo_colors::{DynColors, Effect, Style};
+
+use crate::{cli::ColorWhen, config::ThemeConfig};
+
+/// What a piece of output is, decides the style it gets from the theme.
+#[derive(Debug, Clone, Copy)]
+pub enum Paint {
+    Error,
+    Info,
+    Header,
+    Dirty,
+    Clean,
+}
+
+pub struct Painter {
+    /// Whether stdout gets colors.
+    enabled: bool,
+    /// Whether stderr, which errors are written to, gets colors.
+    enabled_stderr: bool,
+    error: Style,
+    info: Style,
+    header: Style,
+    dirty: Style,
+    clean: Style,
+}
+
+impl Painter {
+    pub fn new(when: ColorWhen, theme: Option<&ThemeConfig>) -> Self {
+        let enabled = |is_terminal: bool| match when {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && is_terminal
+            }
+        };
+        let theme = theme.cloned().unwrap_or_default();
+        let style = |spec: Option<String>, default: Style| {
+            spec.map_or(default, |spec| {
+                parse_style(&spec).unwrap_or_else(|| {
+                    warn!("invalid theme style: '{}'", spec);
+                    default
+                })
+            })
+        };
+        Self {
+            enabled: enabled(std::io::stdout().is_terminal()),
+            enabled_stderr: enabled(std::io::stderr().is_terminal()),
+            error: style(theme.error, Style::new().red().bold()),
+            info: style(theme.info, Style::new().green()),
+            header: style(theme.header, Style::new().bold()),
+            dirty: style(theme.dirty, Style::new().red()),
+            clean: style(theme.clean, Style::new().green()),
+        }
+    }
+
+    pub fn paint(&self, paint: Paint, text: impl Display) -> String {
+        let enabled = match paint {
+            Paint::Error => self.enabled_stderr,
+            _ => self.enabled,
+        };
+        if !enabled {
+            return text.to_string();
+        }
+        let style = match paint {
+            Paint::Error => self.error,
+            Paint::Info => self.info,
+            Paint::Header => self.header,
+            Paint::Dirty => self.dirty,
+            Paint::Clean => self.clean,
+        };
+        style.style(text).to_string()
+    }
+}
+
+/// Parses a style like `bold bright red on black` or `underline #ff8800`.
+fn parse_style(spec: &str) -> Option<Style> {
+    let mut style = Style::new();
+    let mut words = spec.split_whitespace();
+    let mut background = false;
+    while let Some(word) = words.next() {
+        let effect = match word {
+            "bold" => Some(Effect::Bold),
+            "dimmed" => Some(Effect::Dimmed),
+            "italic" => Some(Effect::Italic),
+            "underline" => Some(Effect::Underline),
+            "blink" => Some(Effect::Blink),
+            "reversed" => Some(Effect::Reversed),
+            "strikethrough" => Some(Effect::Strikethrough),
+            _ => None,
+        };
+        if let Some(effect) = effect {
+            style = style.effect(effect);
+            continue;
+        }
+        if word == "on" {
+            background = true;
+            continue;
+        }
+        let color = if word == "bright" {
+            format!("bright {}", words.next()?)
+        } else {
+            word.to_string()
+        };
+        let color = color.parse::<DynColors>().ok()?;
+        style = if background {
+            style.on_color(color)
+        } else {
+            style.color(color)
+        };
+        background = false;
+    }
+    Some(style)
+}
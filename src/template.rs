@@ -0,0 +1,52 @@
+//! Row templates for `--format`, like `{path}\t{hostname}`. `{{` and `}}` are
+//! literal braces, and `\t`, `\n` and `\\` are unescaped.
+
+use anyhow::anyhow;
+
+/// Renders `template` with the values of `fields`, failing on unknown fields.
+pub fn render(template: &str, fields: &[(&str, String)]) -> anyhow::Result<String> {
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let name = chars
+                    .by_ref()
+                    .take_while(|&char| char != '}')
+                    .collect::<String>();
+                let value = fields
+                    .iter()
+                    .find(|(field, _)| *field == name.trim())
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "unknown field {{{}}}, available: {}",
+                            name,
+                            fields
+                                .iter()
+                                .map(|(field, _)| *field)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    })?;
+                output.push_str(value);
+            }
+            '\\' => match chars.next() {
+                Some('t') => output.push('\t'),
+                Some('n') => output.push('\n'),
+                Some(char) => output.push(char),
+                None => output.push('\\'),
+            },
+            char => output.push(char),
+        }
+    }
+    Ok(output)
+}
@@ -0,0 +1,38 @@
+//! Detection of the primary language of repositories from the manifests of
+//! their toolchains.
+
+use std::path::Path;
+
+/// Files in the root of a repository with the language they mark, the first
+/// one found being taken, so `tsconfig.json` wins over `package.json`.
+pub const MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "rust"),
+    ("go.mod", "go"),
+    ("tsconfig.json", "typescript"),
+    ("deno.json", "typescript"),
+    ("package.json", "javascript"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("requirements.txt", "python"),
+    ("Gemfile", "ruby"),
+    ("build.gradle.kts", "kotlin"),
+    ("pom.xml", "java"),
+    ("build.gradle", "java"),
+    ("composer.json", "php"),
+    ("mix.exs", "elixir"),
+    ("pubspec.yaml", "dart"),
+    ("Package.swift", "swift"),
+    ("stack.yaml", "haskell"),
+    ("build.zig", "zig"),
+    ("CMakeLists.txt", "c++"),
+    ("meson.build", "c"),
+];
+
+/// The language of the repository in `dir`, if any of [`MARKERS`] is in it.
+pub fn detect(dir: impl AsRef<Path>) -> Option<&'static str> {
+    let dir = dir.as_ref();
+    MARKERS
+        .iter()
+        .find(|(file, _)| dir.join(file).is_file())
+        .map(|(_, language)| *language)
+}
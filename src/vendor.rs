@@ -0,0 +1,109 @@
+//! The `vendor` repository type, plain directories unpacked from archives.
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use tokio::process::Command;
+use url::Url;
+
+use crate::{
+    http::{self, sha256_hex},
+    meta::{RepoMeta, VendorMeta, META_FILE},
+};
+
+const ARCHIVE_EXTENSIONS: &[&str] = &[
+    ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.bz2", ".tbz2", ".tar.zst", ".tar", ".zip",
+];
+
+/// Splits the url of an archive into the hostname and the path it is placed at.
+pub fn vendor_path(url: &Url) -> anyhow::Result<(String, String)> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("empty host"))?
+        .to_string();
+    let path = url.path().trim_matches('/');
+    let path = ARCHIVE_EXTENSIONS
+        .iter()
+        .find_map(|ext| path.strip_suffix(ext))
+        .unwrap_or(path);
+    Ok((host, path.to_string()))
+}
+
+/// Downloads the archive at `url` and unpacks it into `dest`, replacing what
+/// was unpacked before. Returns `false` if the archive did not change.
+pub async fn fetch(url: &str, dest: &Path) -> anyhow::Result<bool> {
+    let archive = http::download(url).await?;
+    let sha256 = sha256_hex(&archive);
+    let mut meta = RepoMeta::load(dest).await?;
+    if meta
+        .vendor
+        .as_ref()
+        .is_some_and(|vendor| vendor.sha256 == sha256)
+    {
+        return Ok(false);
+    }
+    let name = url.rsplit('/').next().unwrap_or(url);
+    unpack(&archive, name, dest).await?;
+    meta.vendor = Some(VendorMeta {
+        url: url.to_string(),
+        sha256,
+    });
+    meta.save(dest).await?;
+    Ok(true)
+}
+
+async fn unpack(archive: &[u8], name: &str, dest: &Path) -> anyhow::Result<()> {
+    let parent = dest
+        .parent()
+        .ok_or_else(|| anyhow!("invalid destination: {}", dest.display()))?;
+    tokio::fs::create_dir_all(parent).await?;
+    // unpack next to the destination, so the result can be renamed into place
+    let temp_dir = tempfile::tempdir_in(parent)?;
+    let file = temp_dir.path().join(name);
+    let out = temp_dir.path().join("out");
+    tokio::fs::write(&file, archive).await?;
+    tokio::fs::create_dir(&out).await?;
+    let status = if name.ends_with(".zip") {
+        Command::new("unzip")
+            .arg("-q")
+            .arg(&file)
+            .arg("-d")
+            .arg(&out)
+            .status()
+            .await?
+    } else {
+        Command::new("tar")
+            .arg("-xf")
+            .arg(&file)
+            .arg("-C")
+            .arg(&out)
+            .status()
+            .await?
+    };
+    if !status.success() {
+        return Err(anyhow!("unpacking {} failed: {}", name, status));
+    }
+    // archives usually wrap everything in a single top level directory
+    let mut entries = std::fs::read_dir(&out)?.collect::<Result<Vec<_>, _>>()?;
+    let root = match entries.as_slice() {
+        [entry] if entry.file_type()?.is_dir() => entries.remove(0).path(),
+        _ => out,
+    };
+    tokio::fs::create_dir_all(dest).await?;
+    for entry in std::fs::read_dir(dest)? {
+        let entry = entry?;
+        if entry.file_name() == META_FILE {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+        } else {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        tokio::fs::rename(entry.path(), dest.join(entry.file_name())).await?;
+    }
+    Ok(())
+}